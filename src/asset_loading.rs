@@ -1,7 +1,12 @@
-use crate::AppState;
-use bevy::{asset::Asset, ecs::system::SystemParam, gltf::Gltf, prelude::*};
+use crate::{bot, weapon, AppState};
+use bevy::{
+    asset::{Asset, AssetPath, LoadState},
+    ecs::system::SystemParam,
+    gltf::Gltf,
+    prelude::*,
+};
 use bevy_kira_audio::AudioSource;
-use std::marker::PhantomData;
+use std::{fs, marker::PhantomData, path::Path};
 
 pub struct AssetLoadingPlugin;
 impl Plugin for AssetLoadingPlugin {
@@ -36,6 +41,39 @@ impl Default for QueueState {
 #[derive(Default, Resource)]
 pub struct AssetsLoading {
     pub asset_handles: Vec<(HandleUntyped, String)>,
+    /// Paths that couldn't be loaded, shown on the loading error screen
+    pub failed: Vec<String>,
+}
+
+impl AssetsLoading {
+    /// How much of the queue has loaded, from 0 to 1
+    pub fn progress(&self, asset_server: &AssetServer) -> f32 {
+        if self.asset_handles.is_empty() {
+            return 1.0;
+        }
+
+        let loaded = self
+            .asset_handles
+            .iter()
+            .filter(|(handle, _)| asset_server.get_load_state(handle) == LoadState::Loaded)
+            .count();
+        loaded as f32 / self.asset_handles.len() as f32
+    }
+
+    /// Forgets the failures and asks for the failed assets again
+    pub fn retry(&mut self, asset_server: &AssetServer) {
+        for path in self.failed.drain(..) {
+            if self.asset_handles.iter().any(|(_, queued)| *queued == path) {
+                asset_server.reload_asset(path.as_str());
+            }
+        }
+    }
+
+    /// Drops everything that was queued, for when loading is given up on
+    pub fn clear(&mut self) {
+        self.asset_handles.clear();
+        self.failed.clear();
+    }
 }
 
 #[derive(SystemParam)]
@@ -55,6 +93,11 @@ pub struct AssetsHandler<'w, 's> {
 
 impl<'w, 's> AssetsHandler<'w, 's> {
     fn add_asset<T: Asset>(&mut self, asset: &mut Handle<T>, path: &str) {
+        // kept from an earlier load, there's nothing to wait for
+        if self.is_loaded(asset, path) {
+            return;
+        }
+
         *asset = self.asset_server.load(path);
         self.assets_loading
             .asset_handles
@@ -104,10 +147,30 @@ impl<'w, 's> AssetsHandler<'w, 's> {
         self.add_asset(glb, path);
     }
 
+    pub fn add_weapon(&mut self, weapon: &mut Handle<weapon::Weapon>, path: &str) {
+        self.add_asset(weapon, path);
+    }
+
+    pub fn add_bot_profile(&mut self, profile: &mut Handle<bot::BotProfile>, path: &str) {
+        self.add_asset(profile, path);
+    }
+
     pub fn add_animation(&mut self, animation: &mut Handle<AnimationClip>, path: &str) {
         self.add_asset(animation, path);
     }
 
+    /// For assets that can't even be queued, like a missing folder
+    pub fn add_failure(&mut self, path: &str) {
+        if !self
+            .assets_loading
+            .failed
+            .iter()
+            .any(|failed| failed == path)
+        {
+            self.assets_loading.failed.push(path.to_string());
+        }
+    }
+
     pub fn add_standard_mesh(&mut self, handle: &mut Handle<Mesh>, mesh: Mesh) {
         *handle = self.meshes.add(mesh);
     }
@@ -120,7 +183,20 @@ impl<'w, 's> AssetsHandler<'w, 's> {
         *handle = self.materials.add(material);
     }
 
+    /// Whether `handle` already holds the asset at `path`, fully loaded
+    fn is_loaded<T: Asset>(&self, handle: &Handle<T>, path: &str) -> bool {
+        self.asset_server.get_load_state(handle) == LoadState::Loaded
+            && self.asset_server.get_handle_path(handle) == Some(AssetPath::from(path))
+    }
+
     pub fn add_material(&mut self, game_texture: &mut GameTexture, path: &str, transparent: bool) {
+        // the material made for the texture last time is still good
+        if self.is_loaded(&game_texture.image, path)
+            && self.materials.get(&game_texture.material).is_some()
+        {
+            return;
+        }
+
         self.add_asset(&mut game_texture.image, path);
         game_texture.material = self.materials.add(StandardMaterial {
             base_color_texture: Some(game_texture.image.clone()),
@@ -134,14 +210,31 @@ impl<'w, 's> AssetsHandler<'w, 's> {
     }
 }
 
-fn check_assets_ready(mut assets_handler: AssetsHandler) {
-    use bevy::asset::LoadState;
+/// Every `*.<extension>` file in a folder of the assets, named after the file without the
+/// extension and paired with its path from the assets folder
+pub fn folder_assets(folder: &str, extension: &str) -> Vec<(String, String)> {
+    let Ok(entries) = fs::read_dir(Path::new("assets").join(folder)) else {
+        return vec![];
+    };
+
+    let suffix = format!(".{}", extension);
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().into_string().ok()?;
+            let name = file_name.strip_suffix(&suffix)?.to_string();
+            Some((name, format!("{}/{}", folder, file_name)))
+        })
+        .collect()
+}
 
+fn check_assets_ready(mut assets_handler: AssetsHandler) {
     let mut ready = true;
+    let mut failed = vec![];
     for (handle, path) in assets_handler.assets_loading.asset_handles.iter() {
         match assets_handler.asset_server.get_load_state(handle) {
             LoadState::Failed => {
-                panic!("An asset had an error: {:?}", handle);
+                failed.push(path.clone());
             }
             LoadState::Loaded => {}
             LoadState::Unloaded => {
@@ -155,6 +248,19 @@ fn check_assets_ready(mut assets_handler: AssetsHandler) {
         }
     }
 
+    for path in failed {
+        assets_handler.add_failure(&path);
+    }
+
+    if !assets_handler.assets_loading.failed.is_empty() {
+        error!(
+            "Couldn't load {}",
+            assets_handler.assets_loading.failed.join(", ")
+        );
+        assets_handler.next_state.set(AppState::LoadingError);
+        return;
+    }
+
     if ready {
         println!("ready! {:?}", assets_handler.queued_state.state);
         assets_handler.assets_loading.asset_handles = vec![]; // clear list since we've loaded everything
@@ -1,8 +1,9 @@
-use crate::asset_loading;
+use crate::{asset_loading, bot, weapon};
 use bevy::gltf::Gltf;
 use bevy::prelude::*;
 use bevy_kira_audio::AudioSource;
 use bevy_toon_shader::ToonShaderMaterial;
+use std::collections::HashMap;
 
 pub struct AssetsPlugin;
 impl Plugin for AssetsPlugin {
@@ -29,22 +30,22 @@ pub struct GameAssets {
     pub score_font: Handle<Font>,
 
     pub title_bgm: Handle<AudioSource>,
-    pub bgm_1: Handle<AudioSource>,
+    pub level_music: Handle<AudioSource>,
     pub sfx_1: Handle<AudioSource>,
     pub sfx_2: Handle<AudioSource>,
-    pub laser_sfx: Handle<AudioSource>,
-    pub bloop_sfx: Handle<AudioSource>,
     pub smoke_sfx: Handle<AudioSource>,
     pub eliminated_sfx: Handle<AudioSource>,
-    pub candy_hit_sfx: Handle<AudioSource>,
-    pub laser_hit_sfx: Handle<AudioSource>,
     pub fanfare_sfx: Handle<AudioSource>,
 
     pub level: Handle<Gltf>,
+    /// Held while the next round loads in the background so it isn't dropped before it's used
+    pub next_scene: Handle<Gltf>,
+    pub next_background: Handle<Image>,
+    pub next_music: Handle<AudioSource>,
     pub skybox: Handle<Gltf>,
     pub stage: Handle<Gltf>,
-    pub candy: GameMesh,
-    pub laser: GameMesh,
+    pub weapons: HashMap<String, Handle<weapon::Weapon>>,
+    pub bot_profiles: HashMap<String, Handle<bot::BotProfile>>,
     pub bevy_icon: asset_loading::GameTexture,
     pub level_background: asset_loading::GameTexture,
     pub title_screen_background: asset_loading::GameTexture,
@@ -70,9 +71,3 @@ pub struct GameAssets {
     pub gators_logo_texture: asset_loading::GameTexture,
     pub aguas_logo_texture: asset_loading::GameTexture,
 }
-
-#[derive(Default)]
-pub struct GameMesh {
-    pub mesh: Handle<Mesh>,
-    pub texture: asset_loading::GameTexture,
-}
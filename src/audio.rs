@@ -1,5 +1,6 @@
 use bevy::{ecs::system::SystemParam, prelude::*};
 use bevy_kira_audio::{AudioApp, AudioChannel, AudioControl, AudioPlugin, AudioSource};
+use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 
 pub struct GameAudioPlugin;
@@ -7,19 +8,55 @@ impl Plugin for GameAudioPlugin {
     fn build(&self, app: &mut App) {
         app.add_audio_channel::<MusicChannel>()
             .add_audio_channel::<SoundChannel>()
-            .add_plugins(AudioPlugin);
+            .init_resource::<AudioSettings>()
+            .init_resource::<CurrentBgm>()
+            .add_plugins(AudioPlugin)
+            .add_systems(
+                Update,
+                apply_music_volume.run_if(resource_changed::<AudioSettings>()),
+            );
     }
 }
 
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub music_volume: f64,
+    pub sound_volume: f64,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings {
+            music_volume: 0.5,
+            sound_volume: 0.5,
+        }
+    }
+}
+
+fn apply_music_volume(
+    music_channel: Res<AudioChannel<MusicChannel>>,
+    settings: Res<AudioSettings>,
+) {
+    music_channel.set_volume(settings.music_volume);
+}
+
 #[derive(Resource)]
 pub struct MusicChannel;
 #[derive(Resource)]
 pub struct SoundChannel;
 
+/// What the music channel was last told to play
+#[derive(Default, Resource)]
+pub struct CurrentBgm(Handle<AudioSource>);
+
+/// Plays music and sound effects, or nothing when running without audio
 #[derive(SystemParam)]
 pub struct GameAudio<'w, 's> {
-    music_channel: Res<'w, AudioChannel<MusicChannel>>,
-    sound_channel: Res<'w, AudioChannel<SoundChannel>>,
+    music_channel: Option<Res<'w, AudioChannel<MusicChannel>>>,
+    sound_channel: Option<Res<'w, AudioChannel<SoundChannel>>>,
+    current_bgm: Option<ResMut<'w, CurrentBgm>>,
+    settings: Res<'w, AudioSettings>,
 
     #[system_param(ignore)]
     phantom: PhantomData<&'s ()>,
@@ -27,20 +64,46 @@ pub struct GameAudio<'w, 's> {
 
 impl<'w, 's> GameAudio<'w, 's> {
     pub fn play_bgm(&mut self, handle: &Handle<AudioSource>) {
-        self.music_channel.stop();
+        let Some(music_channel) = &self.music_channel else {
+            return;
+        };
+
+        music_channel.stop();
+        if let Some(current_bgm) = &mut self.current_bgm {
+            **current_bgm = CurrentBgm(handle.clone());
+        }
+
         #[cfg(not(feature = "no_music"))]
         {
-            self.music_channel.set_volume(0.5);
-            self.music_channel.play(handle.clone()).looped();
+            music_channel.set_volume(self.settings.music_volume);
+            music_channel.play(handle.clone()).looped();
         }
     }
 
+    /// Like [`Self::play_bgm`] but leaves the music going if it's already playing `handle`
+    pub fn continue_bgm(&mut self, handle: &Handle<AudioSource>) {
+        if let Some(current_bgm) = &self.current_bgm {
+            if current_bgm.0 == *handle {
+                return;
+            }
+        }
+
+        self.play_bgm(handle);
+    }
+
     pub fn stop_bgm(&mut self) {
-        self.music_channel.stop();
+        if let Some(music_channel) = &self.music_channel {
+            music_channel.stop();
+        }
+        if let Some(current_bgm) = &mut self.current_bgm {
+            **current_bgm = CurrentBgm::default();
+        }
     }
 
     pub fn play_sfx(&mut self, handle: &Handle<AudioSource>) {
-        self.sound_channel.set_volume(0.5);
-        self.sound_channel.play(handle.clone());
+        if let Some(sound_channel) = &self.sound_channel {
+            sound_channel.set_volume(self.settings.sound_volume);
+            sound_channel.play(handle.clone());
+        }
     }
 }
@@ -0,0 +1,257 @@
+use super::{nodes, BotProfile, Cardinal};
+use crate::{burro, floor, player::AimMode, weapon};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::rngs::StdRng;
+
+// how close a bot needs to get to a waypoint before heading for the next one
+const WAYPOINT_RADIUS: f32 = 0.5;
+
+/// The tree every bot ticks each time it thinks. A game mode can insert its own
+/// to give bots different goals, see [`nodes::default_tree`] for the usual one
+#[derive(Resource)]
+pub struct BotBehaviour(pub Node);
+
+impl Default for BotBehaviour {
+    fn default() -> Self {
+        BotBehaviour(nodes::default_tree())
+    }
+}
+
+/// What a node tells its parent after being ticked
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Status {
+    Success,
+    Failure,
+}
+
+impl From<bool> for Status {
+    fn from(success: bool) -> Self {
+        if success {
+            Status::Success
+        } else {
+            Status::Failure
+        }
+    }
+}
+
+type Condition = Box<dyn Fn(&BotContext) -> bool + Send + Sync>;
+type Action = Box<dyn Fn(&mut BotContext) -> Status + Send + Sync>;
+
+pub enum Node {
+    /// Ticks its children in order until one of them fails
+    Sequence(Vec<Node>),
+    /// Ticks its children in order until one of them succeeds
+    Selector(Vec<Node>),
+    /// Checks something without changing anything
+    Condition(Condition),
+    /// Changes the bot's blackboard, failing if it couldn't
+    Action(Action),
+}
+
+impl Node {
+    pub fn sequence(children: impl IntoIterator<Item = Node>) -> Self {
+        Node::Sequence(children.into_iter().collect())
+    }
+
+    pub fn selector(children: impl IntoIterator<Item = Node>) -> Self {
+        Node::Selector(children.into_iter().collect())
+    }
+
+    pub fn condition(check: impl Fn(&BotContext) -> bool + Send + Sync + 'static) -> Self {
+        Node::Condition(Box::new(check))
+    }
+
+    pub fn action(act: impl Fn(&mut BotContext) -> Status + Send + Sync + 'static) -> Self {
+        Node::Action(Box::new(act))
+    }
+
+    /// Runs `node` but always succeeds, so a failure doesn't stop the rest of a sequence
+    pub fn optional(node: Node) -> Self {
+        Node::selector([node, Node::condition(|_| true)])
+    }
+
+    pub fn tick(&self, context: &mut BotContext) -> Status {
+        match self {
+            Node::Sequence(children) => {
+                for child in children {
+                    if child.tick(context) == Status::Failure {
+                        return Status::Failure;
+                    }
+                }
+                Status::Success
+            }
+            Node::Selector(children) => {
+                for child in children {
+                    if child.tick(context) == Status::Success {
+                        return Status::Success;
+                    }
+                }
+                Status::Failure
+            }
+            Node::Condition(check) => check(context).into(),
+            Node::Action(act) => act(context),
+        }
+    }
+}
+
+/// Everything a bot's nodes can see while it thinks
+pub struct BotContext<'a> {
+    pub entity: Entity,
+    pub burro: &'a burro::Burro,
+    /// On the ground plane, x and z
+    pub position: Vec2,
+    /// How high the bot's bullets fly
+    pub shot_height: f32,
+    pub profile: &'a BotProfile,
+    pub blackboard: &'a mut Blackboard,
+    /// The burros that can be hit, closest first
+    pub others: &'a [OtherBurro],
+    pub bullets: &'a [FlyingBullet],
+    /// What the bot fires, if it's loaded
+    pub weapon: Option<&'a weapon::Weapon>,
+    pub aim_mode: AimMode,
+    /// How close a bullet has to get to a burro to hit it
+    pub bullet_distance: f32,
+    pub floor_manager: &'a floor::FloorManager,
+    pub rapier_context: &'a RapierContext,
+    pub rng: &'a mut StdRng,
+}
+
+/// Another burro as a bot sees it, positions and velocities are on the ground plane
+pub struct OtherBurro {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub distance: f32,
+    pub is_mechaburro: bool,
+}
+
+/// A bullet as a bot sees it, positions and velocities are on the ground plane
+pub struct FlyingBullet {
+    pub source: Entity,
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub time_left: f32,
+}
+
+/// Why a bot is heading for its target
+#[derive(Default, Copy, Clone, PartialEq, Debug)]
+pub enum Plan {
+    #[default]
+    Wander,
+    Chase,
+    Flee,
+    LineUp,
+}
+
+/// What a bot remembers between thoughts. Its nodes decide where it goes and shoots
+/// and it steers along its path every tick in between
+#[derive(Default)]
+pub struct Blackboard {
+    pub heading: Option<Cardinal>,
+    /// Direction to shoot in on the ground plane, x and z
+    pub shooting: Option<Vec2>,
+    pub target: Option<Vec2>,
+    pub plan: Plan,
+    pub wander_cooldown: f32,
+    /// Whether the mechaburro is close, so the bot only decides to run once each time
+    pub is_wary: bool,
+    /// While this is above zero the bot is sidestepping a bullet instead of following its path
+    pub dodge_time: f32,
+    mind_cooldown: f32,
+    // waypoints to the target from the floor manager, the next one is last
+    pub(super) path: Vec<Vec2>,
+    previous_distance: f32,
+}
+
+impl Blackboard {
+    pub fn can_think(&self) -> bool {
+        self.mind_cooldown <= 0.0
+    }
+
+    pub fn is_dodging(&self) -> bool {
+        self.dodge_time > 0.0
+    }
+
+    /// Counts down the timers and steers along the path, returns whether the bot gets
+    /// to think this tick. Thinking again waits for the bot's reaction time
+    pub fn tick(&mut self, delta: f32, position: Vec2, reaction_time: f32) -> bool {
+        self.mind_cooldown -= delta;
+        self.mind_cooldown = self.mind_cooldown.clamp(-10.0, 30.0);
+        self.wander_cooldown -= delta;
+        self.dodge_time -= delta;
+
+        // a sidestep keeps its heading until it's done
+        if !self.is_dodging() {
+            self.follow_path(position);
+        }
+
+        if !self.can_think() {
+            return false;
+        }
+
+        self.mind_cooldown = reaction_time;
+        true
+    }
+
+    pub fn stop(&mut self) {
+        self.heading = None;
+        self.target = None;
+        self.path.clear();
+        self.mind_cooldown = 0.0;
+        self.plan = Plan::Wander;
+    }
+
+    /// Plans a path to `target`, keeping the current one if there's no way there
+    pub fn go_to(
+        &mut self,
+        floor_manager: &floor::FloorManager,
+        position: Vec2,
+        target: Vec2,
+        plan: Plan,
+    ) -> bool {
+        let Some(mut path) = floor_manager.find_path(position, target) else {
+            return false;
+        };
+
+        // the next waypoint goes last so it can be popped off
+        path.reverse();
+        self.path = path;
+        self.target = Some(target);
+        self.previous_distance = 0.0;
+        self.plan = plan;
+
+        true
+    }
+
+    /// Steers toward the next waypoint, this happens every tick even while the bot isn't thinking
+    fn follow_path(&mut self, position: Vec2) {
+        if self.target.is_none() {
+            return;
+        }
+
+        // drop the waypoints that have been reached
+        while let Some(waypoint) = self.path.last() {
+            if position.distance(*waypoint) >= WAYPOINT_RADIUS {
+                break;
+            }
+            self.path.pop();
+        }
+
+        let Some(waypoint) = self.path.last().copied() else {
+            self.stop();
+            return;
+        };
+
+        let distance = position.distance(waypoint);
+        let is_bot_stuck = (self.previous_distance - distance).abs() < 0.001;
+        self.previous_distance = distance;
+
+        if is_bot_stuck {
+            // something's in the way, pick somewhere else to go
+            self.stop();
+        } else {
+            self.heading = Cardinal::towards(waypoint - position);
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use super::{Bot, Cardinal};
+use crate::{floor, game_camera::PanOrbitCamera, AppState};
+use bevy::prelude::*;
+
+// lifts the overlay off whatever it's drawn on so it doesn't flicker
+const OVERLAY_OFFSET: f32 = 0.05;
+const TARGET_RADIUS: f32 = 0.5;
+const HEADING_LENGTH: f32 = 1.5;
+const SHOOTING_LENGTH: f32 = 3.0;
+const LABEL_FONT_SIZE: f32 = 20.0;
+
+/// Draws what each bot is thinking, toggled with F3
+pub struct BotDebugPlugin;
+impl Plugin for BotDebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BotDebugOverlay { is_visible: true })
+            .add_systems(Update, toggle_overlay)
+            .add_systems(
+                Update,
+                (draw_overlay, update_labels).run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), remove_labels);
+    }
+}
+
+#[derive(Resource)]
+struct BotDebugOverlay {
+    is_visible: bool,
+}
+
+#[derive(Component)]
+struct DebugLabel {
+    bot: Entity,
+}
+
+// on a bot once its label is spawned
+#[derive(Component)]
+struct HasDebugLabel;
+
+fn toggle_overlay(keys: Res<Input<KeyCode>>, mut overlay: ResMut<BotDebugOverlay>) {
+    if keys.just_pressed(KeyCode::F3) {
+        overlay.is_visible = !overlay.is_visible;
+    }
+}
+
+fn draw_overlay(
+    mut gizmos: Gizmos,
+    overlay: Res<BotDebugOverlay>,
+    bots: Query<(&Bot, &Transform)>,
+    floor_manager: Res<floor::FloorManager>,
+) {
+    if !overlay.is_visible {
+        return;
+    }
+
+    for (min, max) in floor_manager.floors() {
+        draw_bounds(&mut gizmos, min, max, Color::GREEN);
+    }
+    for (min, max) in floor_manager.walls() {
+        draw_bounds(&mut gizmos, min, max, Color::ORANGE_RED);
+    }
+
+    for (bot, transform) in bots.iter() {
+        let blackboard = &bot.blackboard;
+        let position = transform.translation;
+        let height = position.y + OVERLAY_OFFSET;
+        let on_ground = |point: Vec2| Vec3::new(point.x, height, point.y);
+
+        // the next waypoint is last
+        let path = std::iter::once(position)
+            .chain(blackboard.path.iter().rev().map(|point| on_ground(*point)))
+            .collect::<Vec<_>>();
+        gizmos.linestrip(path, Color::YELLOW);
+
+        if let Some(target) = blackboard.target {
+            gizmos.circle(on_ground(target), Vec3::Y, TARGET_RADIUS, Color::YELLOW);
+        }
+
+        if let Some(heading) = blackboard.heading {
+            let direction = heading_direction(heading);
+            gizmos.ray(
+                position,
+                Vec3::new(direction.x, 0.0, direction.y) * HEADING_LENGTH,
+                Color::CYAN,
+            );
+        }
+
+        if let Some(shooting) = blackboard.shooting {
+            let direction = shooting.normalize_or_zero();
+            gizmos.ray(
+                position,
+                Vec3::new(direction.x, 0.0, direction.y) * SHOOTING_LENGTH,
+                Color::RED,
+            );
+        }
+    }
+}
+
+fn draw_bounds(gizmos: &mut Gizmos, min: Vec3, max: Vec3, color: Color) {
+    let height = max.y + OVERLAY_OFFSET;
+    gizmos.linestrip(
+        [
+            Vec3::new(min.x, height, min.z),
+            Vec3::new(max.x, height, min.z),
+            Vec3::new(max.x, height, max.z),
+            Vec3::new(min.x, height, max.z),
+            Vec3::new(min.x, height, min.z),
+        ],
+        color,
+    );
+}
+
+fn heading_direction(heading: Cardinal) -> Vec2 {
+    let direction = match heading {
+        Cardinal::N => Vec2::new(1.0, 0.0),
+        Cardinal::S => Vec2::new(-1.0, 0.0),
+        Cardinal::E => Vec2::new(0.0, 1.0),
+        Cardinal::W => Vec2::new(0.0, -1.0),
+        Cardinal::NE => Vec2::new(1.0, 1.0),
+        Cardinal::NW => Vec2::new(1.0, -1.0),
+        Cardinal::SE => Vec2::new(-1.0, 1.0),
+        Cardinal::SW => Vec2::new(-1.0, -1.0),
+    };
+
+    direction.normalize()
+}
+
+/// What the bot is up to in a couple of words
+fn decision(bot: &Bot) -> String {
+    let blackboard = &bot.blackboard;
+    let mut decision = if blackboard.is_dodging() {
+        "Dodge".to_string()
+    } else if blackboard.target.is_some() {
+        format!("{:?}", blackboard.plan)
+    } else {
+        "Idle".to_string()
+    };
+
+    if blackboard.shooting.is_some() {
+        decision.push_str(" + Shoot");
+    }
+
+    decision
+}
+
+fn update_labels(
+    mut commands: Commands,
+    overlay: Res<BotDebugOverlay>,
+    new_bots: Query<Entity, (With<Bot>, Without<HasDebugLabel>)>,
+    bots: Query<(&Bot, &Transform)>,
+    mut labels: Query<(Entity, &DebugLabel, &mut Text, &mut Style, &mut Visibility)>,
+    camera: Query<(&Camera, &GlobalTransform), With<PanOrbitCamera>>,
+) {
+    for bot in new_bots.iter() {
+        commands.entity(bot).insert(HasDebugLabel);
+        commands.spawn((
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: LABEL_FONT_SIZE,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                ..default()
+            })
+            .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.5)),
+            DebugLabel { bot },
+        ));
+    }
+
+    let camera = camera.get_single().ok();
+    for (entity, label, mut text, mut style, mut visibility) in labels.iter_mut() {
+        let Ok((bot, transform)) = bots.get(label.bot) else {
+            // the bot is gone
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+
+        // hangs down from the burro's middle, clear of the player labels above it
+        let coords = camera.and_then(|(camera, camera_transform)| {
+            camera.world_to_viewport(camera_transform, transform.translation)
+        });
+        let Some(coords) = coords.filter(|_| overlay.is_visible) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        *visibility = Visibility::Inherited;
+        text.sections[0].value = decision(bot);
+        style.left = Val::Px(coords.x);
+        style.top = Val::Px(coords.y);
+    }
+}
+
+fn remove_labels(
+    mut commands: Commands,
+    labels: Query<Entity, With<DebugLabel>>,
+    bots: Query<Entity, With<HasDebugLabel>>,
+) {
+    for entity in labels.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    // pausing leaves the match too, the labels come back after it
+    for entity in bots.iter() {
+        commands.entity(entity).remove::<HasDebugLabel>();
+    }
+}
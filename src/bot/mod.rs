@@ -0,0 +1,305 @@
+use crate::{assets, bullet, burro, config, floor, player::PlayerAction, rng, simulation, weapon};
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::BoxedFuture,
+};
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::{axislike::DualAxisData, prelude::*};
+use serde::Deserialize;
+use std::f32::consts::PI;
+
+pub mod behaviour;
+#[cfg(feature = "bot_debug")]
+pub mod debug;
+pub mod nodes;
+
+pub const BOT_PROFILE_EXTENSION: &str = "bot.ron";
+/// The profile a bot plays with when none was picked for it
+pub const DEFAULT_PROFILE: &str = "normal";
+
+// bullets are fired from this far above a burro's position
+const SHOT_HEIGHT: f32 = 0.5;
+
+pub struct BotPlugin;
+
+impl Plugin for BotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<BotProfile>()
+            .init_asset_loader::<BotProfileLoader>()
+            .init_resource::<behaviour::BotBehaviour>();
+    }
+}
+
+/// How a bot plays, loaded from a `*.bot.ron` file in `assets/bots` and named after it
+#[derive(TypeUuid, TypePath, Clone, Deserialize)]
+#[uuid = "b3e9f0c4-2d71-4a8e-9c65-7f1a0d3b8e52"]
+#[serde(default)]
+pub struct BotProfile {
+    /// Seconds between decisions, where to go and where to shoot
+    pub reaction_time: f32,
+    /// How far off a shot can be, in units. Sloppier bots shoot more often and miss more
+    pub aim_tolerance: f32,
+    /// Chance of going after the closest burro instead of wandering, from 0 to 1
+    pub aggression: f32,
+    /// Chance of running away when the mechaburro gets close, from 0 to 1
+    pub flee_mechaburro: f32,
+    /// How many times a second the bot changes its mind about where it's going, 0 never does
+    pub wander_frequency: f32,
+    /// How soon a bullet coming at the bot is seen so it can step aside, from 0 to 1.
+    /// 0 never dodges
+    pub dodge_skill: f32,
+}
+
+// the same as assets/bots/normal.bot.ron, for a bot whose profile hasn't loaded
+impl Default for BotProfile {
+    fn default() -> Self {
+        BotProfile {
+            reaction_time: 0.25,
+            aim_tolerance: 0.75,
+            aggression: 0.3,
+            flee_mechaburro: 0.5,
+            wander_frequency: 0.15,
+            dodge_skill: 0.5,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct BotProfileLoader;
+impl AssetLoader for BotProfileLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let profile: BotProfile = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(profile));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[BOT_PROFILE_EXTENSION]
+    }
+}
+
+#[derive(Component, Default)]
+pub struct Bot {
+    profile: Handle<BotProfile>,
+    blackboard: behaviour::Blackboard,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Cardinal {
+    N,
+    S,
+    E,
+    W,
+    NE,
+    NW,
+    SE,
+    SW,
+}
+
+impl Cardinal {
+    /// The closest of the eight headings to a direction on the ground plane, north is +x
+    pub fn towards(direction: Vec2) -> Option<Cardinal> {
+        // anything within 22.5° of an axis counts as that axis
+        let threshold = (PI / 8.0).sin();
+        let direction = direction.normalize_or_zero();
+        let x = if direction.x > threshold {
+            1
+        } else if direction.x < -threshold {
+            -1
+        } else {
+            0
+        };
+        let z = if direction.y > threshold {
+            1
+        } else if direction.y < -threshold {
+            -1
+        } else {
+            0
+        };
+
+        match (x, z) {
+            (1, 0) => Some(Cardinal::N),
+            (-1, 0) => Some(Cardinal::S),
+            (0, 1) => Some(Cardinal::E),
+            (0, -1) => Some(Cardinal::W),
+            (1, 1) => Some(Cardinal::NE),
+            (1, -1) => Some(Cardinal::NW),
+            (-1, 1) => Some(Cardinal::SE),
+            (-1, -1) => Some(Cardinal::SW),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Bundle)]
+pub struct BotBundle {
+    bot: Bot,
+    input_manager: InputManagerBundle<PlayerAction>,
+}
+
+impl BotBundle {
+    /// A bot playing the named profile, or the default one if there's no profile by that name
+    pub fn new(game_assets: &assets::GameAssets, profile: Option<&str>) -> Self {
+        let name = profile.unwrap_or(DEFAULT_PROFILE);
+        let profile = match game_assets.bot_profiles.get(name) {
+            Some(profile) => profile.clone(),
+            None => {
+                warn!("No bot profile named {}, using the defaults", name);
+                Handle::default()
+            }
+        };
+
+        BotBundle {
+            bot: Bot {
+                profile,
+                ..default()
+            },
+            input_manager: InputManagerBundle {
+                input_map: InputMap::default(),
+                action_state: ActionState::default(),
+            },
+        }
+    }
+}
+
+pub fn update_bot_ai(
+    fixed_time: Res<FixedTime>,
+    mut bots: Query<(Entity, &mut Bot, &burro::Burro, &Transform)>,
+    other_burros: Query<(Entity, &Transform, &burro::Burro)>,
+    bullets: Query<(&bullet::Bullet, &Transform)>,
+    bot_behaviour: Res<behaviour::BotBehaviour>,
+    floor_manager: Res<floor::FloorManager>,
+    match_rules: Res<config::MatchRules>,
+    game_config: Res<config::GameConfiguration>,
+    game_assets: Res<assets::GameAssets>,
+    weapons: Res<Assets<weapon::Weapon>>,
+    profiles: Res<Assets<BotProfile>>,
+    rapier_context: Res<RapierContext>,
+    mut game_rng: ResMut<rng::GameRng>,
+) {
+    let default_profile = BotProfile::default();
+    let delta = simulation::tick_delta(&fixed_time);
+    let bullets: Vec<_> = bullets
+        .iter()
+        .map(|(bullet, transform)| behaviour::FlyingBullet {
+            source: bullet.source(),
+            position: Vec2::new(transform.translation.x, transform.translation.z),
+            velocity: Vec2::new(bullet.velocity().x, bullet.velocity().z),
+            time_left: bullet.time_left(),
+        })
+        .collect();
+
+    for (entity, mut bot, burro, transform) in bots.iter_mut() {
+        let bot = &mut *bot;
+        let profile = profiles.get(&bot.profile).unwrap_or(&default_profile);
+        let burro_position = Vec2::new(transform.translation.x, transform.translation.z);
+
+        // the bot steers along its path every tick but only runs its tree when it can think
+        if !bot
+            .blackboard
+            .tick(delta, burro_position, profile.reaction_time)
+        {
+            continue;
+        }
+
+        let mut others: Vec<_> = other_burros
+            .iter()
+            .filter(|(other_entity, _, other_burro)| {
+                entity != *other_entity && other_burro.can_be_hit()
+            }) // skip yourself and burros that can't be hit
+            .map(|(_, other_transform, other_burro)| {
+                let position =
+                    Vec2::new(other_transform.translation.x, other_transform.translation.z);
+                behaviour::OtherBurro {
+                    position,
+                    velocity: Vec2::new(other_burro.velocity.x, other_burro.velocity.z),
+                    distance: position.distance(burro_position),
+                    is_mechaburro: other_burro.is_mechaburro,
+                }
+            })
+            .collect();
+
+        others.sort_by_key(|other| other.distance as usize); // sort by distance to self
+
+        let weapon = game_assets
+            .weapons
+            .get(game_config.weapon_name(burro.is_mechaburro))
+            .and_then(|weapon| weapons.get(weapon));
+
+        let mut context = behaviour::BotContext {
+            entity,
+            burro,
+            position: burro_position,
+            shot_height: transform.translation.y + SHOT_HEIGHT,
+            profile,
+            blackboard: &mut bot.blackboard,
+            others: &others,
+            bullets: &bullets,
+            weapon,
+            aim_mode: match_rules.aim_mode,
+            bullet_distance: game_config.bullet_distance,
+            floor_manager: &floor_manager,
+            rapier_context: &rapier_context,
+            rng: &mut game_rng.gameplay,
+        };
+        bot_behaviour.0.tick(&mut context);
+    }
+}
+
+pub fn update_virtual_controllers(mut bots: Query<(Entity, &Bot, &mut ActionState<PlayerAction>)>) {
+    for (_, bot, mut action_state) in bots.iter_mut() {
+        // release all buttons
+        // this probably affects durations but for
+        // this game it might not be a big deal
+        action_state.release(PlayerAction::Up);
+        action_state.release(PlayerAction::Down);
+        action_state.release(PlayerAction::Left);
+        action_state.release(PlayerAction::Right);
+
+        action_state.release(PlayerAction::ActionUp);
+        action_state.release(PlayerAction::ActionDown);
+        action_state.release(PlayerAction::ActionLeft);
+        action_state.release(PlayerAction::ActionRight);
+        action_state.release(PlayerAction::Shoot);
+
+        if let Some(cardinal) = &bot.blackboard.heading {
+            match cardinal {
+                Cardinal::N => action_state.press(PlayerAction::Up),
+                Cardinal::S => action_state.press(PlayerAction::Down),
+                Cardinal::W => action_state.press(PlayerAction::Left),
+                Cardinal::E => action_state.press(PlayerAction::Right),
+                Cardinal::NE => {
+                    action_state.press(PlayerAction::Up);
+                    action_state.press(PlayerAction::Right);
+                }
+                Cardinal::NW => {
+                    action_state.press(PlayerAction::Up);
+                    action_state.press(PlayerAction::Left);
+                }
+                Cardinal::SE => {
+                    action_state.press(PlayerAction::Down);
+                    action_state.press(PlayerAction::Right);
+                }
+                Cardinal::SW => {
+                    action_state.press(PlayerAction::Down);
+                    action_state.press(PlayerAction::Left);
+                }
+            }
+        }
+
+        if let Some(shooting) = bot.blackboard.shooting {
+            // push the right stick, its x is the ground's z
+            action_state.press(PlayerAction::Shoot);
+            action_state.action_data_mut(PlayerAction::Shoot).axis_pair =
+                Some(DualAxisData::new(shooting.y, shooting.x));
+        }
+    }
+}
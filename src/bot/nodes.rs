@@ -0,0 +1,480 @@
+use super::{
+    behaviour::{BotContext, FlyingBullet, Node, Plan, Status},
+    Cardinal,
+};
+use crate::{bullet, floor, player::AimMode};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+
+// how close a burro needs to be before a bot will take a free aimed shot at it
+const FREE_AIM_RANGE: f32 = 8.0;
+// how close the mechaburro needs to get before a bot thinks about running
+const FLEE_RANGE: f32 = 10.0;
+// a fleeing bot heads for whichever of this many random spots is furthest from the mechaburro
+const FLEE_SPOTS: usize = 5;
+// how many seconds ahead a bot with the best dodge skill sees a bullet coming
+const MAX_DODGE_WARNING: f32 = 1.0;
+// how long a sidestep lasts
+const DODGE_TIME: f32 = 0.3;
+// how far along a sidestep to check for walls and gaps
+const DODGE_DISTANCE: f32 = 1.5;
+// how close the closest burro needs to be before a bot moves to line up a shot at it
+const LINE_UP_RANGE: f32 = 12.0;
+// lining up any closer than this would be walking into the other burro's line of fire
+const MIN_LINE_UP_DISTANCE: f32 = 3.0;
+
+/// How bots play a normal round. Each thought they dodge, keep away from the mechaburro,
+/// pick somewhere new to go when they need to, shoot and line up a shot if they couldn't
+pub fn default_tree() -> Node {
+    Node::sequence([
+        Node::optional(Node::sequence([
+            Node::condition(can_dodge),
+            Node::action(dodge_bullets),
+        ])),
+        Node::optional(Node::action(flee_mechaburro)),
+        Node::optional(Node::sequence([
+            Node::condition(needs_destination),
+            Node::selector([Node::action(chase), Node::action(wander)]),
+        ])),
+        Node::optional(Node::action(shoot)),
+        Node::optional(Node::sequence([
+            Node::condition(can_line_up),
+            Node::action(line_up),
+        ])),
+    ])
+}
+
+pub fn can_dodge(context: &BotContext) -> bool {
+    !context.blackboard.is_dodging() && context.profile.dodge_skill > 0.0
+}
+
+/// Sidesteps the first bullet that will pass close by, better bots see bullets coming sooner
+pub fn dodge_bullets(context: &mut BotContext) -> Status {
+    let warning = context.profile.dodge_skill.clamp(0.0, 1.0) * MAX_DODGE_WARNING;
+    let dodge = find_dodge(
+        context.entity,
+        context.position,
+        context.bullets,
+        context.bullet_distance,
+        warning,
+        context.floor_manager,
+    );
+
+    let Some(direction) = dodge else {
+        return Status::Failure;
+    };
+    context.blackboard.heading = Cardinal::towards(direction);
+    context.blackboard.dodge_time = DODGE_TIME;
+
+    Status::Success
+}
+
+/// Decides whether to run from the mechaburro once each time it comes close,
+/// succeeds if the bot runs
+pub fn flee_mechaburro(context: &mut BotContext) -> Status {
+    let mechaburro = context
+        .others
+        .iter()
+        .find(|other| other.is_mechaburro && other.distance < FLEE_RANGE)
+        .map(|other| other.position);
+
+    let Some(mechaburro) = mechaburro else {
+        context.blackboard.is_wary = false;
+        return Status::Failure;
+    };
+    if context.blackboard.is_wary || context.burro.is_mechaburro {
+        return Status::Failure;
+    }
+
+    context.blackboard.is_wary = true;
+    if context.rng.gen::<f32>() >= context.profile.flee_mechaburro {
+        return Status::Failure;
+    }
+
+    let spot = (0..FLEE_SPOTS)
+        .filter_map(|_| context.floor_manager.get_random_spot(context.rng))
+        .max_by(|a, b| {
+            a.distance_squared(mechaburro)
+                .total_cmp(&b.distance_squared(mechaburro))
+        });
+    let Some(spot) = spot else {
+        return Status::Failure;
+    };
+
+    context
+        .blackboard
+        .go_to(context.floor_manager, context.position, spot, Plan::Flee)
+        .into()
+}
+
+/// Whether the bot has nowhere to go or it's time for it to change its mind
+pub fn needs_destination(context: &BotContext) -> bool {
+    let wants_to_wander =
+        context.profile.wander_frequency > 0.0 && context.blackboard.wander_cooldown <= 0.0;
+
+    context.blackboard.target.is_none() || wants_to_wander
+}
+
+/// Goes after the closest burro, depending on how aggressive the bot is
+pub fn chase(context: &mut BotContext) -> Status {
+    let Some(closest_burro) = context.others.first().map(|other| other.position) else {
+        return Status::Failure;
+    };
+    if context.rng.gen::<f32>() >= context.profile.aggression {
+        return Status::Failure;
+    }
+
+    let is_going = context.blackboard.go_to(
+        context.floor_manager,
+        context.position,
+        closest_burro,
+        Plan::Chase,
+    );
+    if is_going {
+        reset_wander_cooldown(context);
+    }
+
+    is_going.into()
+}
+
+/// Heads off to a random spot on the floor
+pub fn wander(context: &mut BotContext) -> Status {
+    reset_wander_cooldown(context);
+
+    let Some(spot) = context.floor_manager.get_random_spot(context.rng) else {
+        return Status::Failure;
+    };
+
+    context
+        .blackboard
+        .go_to(context.floor_manager, context.position, spot, Plan::Wander)
+        .into()
+}
+
+fn reset_wander_cooldown(context: &mut BotContext) {
+    context.blackboard.wander_cooldown = 1.0 / context.profile.wander_frequency.max(f32::EPSILON);
+}
+
+/// Shoots at the closest burro there's a clear shot at, leading it if it's moving
+pub fn shoot(context: &mut BotContext) -> Status {
+    context.blackboard.shooting = None;
+    if !context.burro.can_fire() {
+        return Status::Failure;
+    }
+
+    let passes_walls = context.weapon.map_or(false, |weapon| {
+        weapon.wall_collision == bullet::WallCollision::PassThrough
+    });
+
+    for other in context.others {
+        let aim_point = context.weapon.map_or(other.position, |weapon| {
+            lead(
+                context.position,
+                other.position,
+                other.velocity,
+                weapon.speed,
+            )
+        });
+
+        let Some(direction) = aim(
+            context.aim_mode,
+            context.profile.aim_tolerance,
+            context.position,
+            aim_point,
+            context.rng,
+        ) else {
+            continue;
+        };
+
+        // how far the bullet travels before passing the aim point
+        let reach = (aim_point - context.position).dot(direction).max(0.0);
+        if passes_walls
+            || has_line_of_sight(
+                context.rapier_context,
+                context.position,
+                direction,
+                reach,
+                context.shot_height,
+            )
+        {
+            context.blackboard.shooting = Some(direction);
+            return Status::Success;
+        }
+    }
+
+    Status::Failure
+}
+
+/// Whether the bot could shoot but has no shot and isn't busy going somewhere more important
+pub fn can_line_up(context: &BotContext) -> bool {
+    let is_busy = context.blackboard.is_dodging()
+        || matches!(context.blackboard.plan, Plan::Flee | Plan::LineUp);
+
+    context.blackboard.shooting.is_none() && context.burro.can_fire() && !is_busy
+}
+
+/// Rather than waiting for a shot to line up, goes somewhere that has one at the closest burro
+pub fn line_up(context: &mut BotContext) -> Status {
+    let Some(closest_burro) = context
+        .others
+        .first()
+        .filter(|other| other.distance < LINE_UP_RANGE)
+    else {
+        return Status::Failure;
+    };
+
+    let spot = aligned_spot(
+        context.aim_mode,
+        context.position,
+        closest_burro.position,
+        context.shot_height,
+        context.floor_manager,
+        context.rapier_context,
+    );
+    let Some(spot) = spot else {
+        return Status::Failure;
+    };
+
+    context
+        .blackboard
+        .go_to(context.floor_manager, context.position, spot, Plan::LineUp)
+        .into()
+}
+
+/// Where to aim so a bullet meets a burro that keeps moving the same way,
+/// or right at it when the bullet can't catch up
+fn lead(position: Vec2, target: Vec2, velocity: Vec2, bullet_speed: f32) -> Vec2 {
+    // solves |offset + velocity * t| = bullet_speed * t for the soonest t
+    let offset = target - position;
+    let a = velocity.length_squared() - bullet_speed * bullet_speed;
+    let b = 2.0 * offset.dot(velocity);
+    let c = offset.length_squared();
+
+    let time = if a.abs() < f32::EPSILON {
+        // the burro is as fast as the bullet, it's only caught if it's coming closer
+        (b < 0.0).then(|| -c / b)
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            None
+        } else {
+            let root = discriminant.sqrt();
+            [(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+                .into_iter()
+                .filter(|time| *time > 0.0)
+                .reduce(f32::min)
+        }
+    };
+
+    time.map_or(target, |time| target + velocity * time)
+}
+
+/// The direction to shoot in to hit `aim_point`, if the aim mode allows a shot that's
+/// close enough. Sloppier bots take shots that are further off
+fn aim(
+    aim_mode: AimMode,
+    tolerance: f32,
+    position: Vec2,
+    aim_point: Vec2,
+    rng: &mut impl Rng,
+) -> Option<Vec2> {
+    let tolerance = tolerance.max(0.0);
+    let x_diff = aim_point.x - position.x;
+    let z_diff = aim_point.y - position.y;
+
+    if aim_mode == AimMode::Free {
+        // aim at the burro if it's close enough, sloppy bots aim somewhere near it
+        if position.distance(aim_point) >= FREE_AIM_RANGE {
+            return None;
+        }
+
+        let miss = Vec2::new(
+            rng.gen_range(-tolerance..=tolerance),
+            rng.gen_range(-tolerance..=tolerance),
+        );
+        return Some((aim_point + miss - position).normalize_or_zero());
+    }
+
+    // shoot diagonally to try to hit a burro
+    if aim_mode == AimMode::EightWay
+        && x_diff.abs() > tolerance
+        && (x_diff.abs() - z_diff.abs()).abs() <= tolerance
+    {
+        return Some(Vec2::new(x_diff.signum(), z_diff.signum()).normalize());
+    }
+
+    // shoot up or down to try to hit a burro
+    if z_diff.abs() <= tolerance && x_diff != 0.0 {
+        return Some(Vec2::new(x_diff.signum(), 0.0));
+    }
+
+    // shoot left or right to try to hit a burro
+    if x_diff.abs() <= tolerance && z_diff != 0.0 {
+        return Some(Vec2::new(0.0, z_diff.signum()));
+    }
+
+    None
+}
+
+/// Whether a bullet fired from `position` would get `distance` along `direction`
+/// without hitting the level
+fn has_line_of_sight(
+    rapier_context: &RapierContext,
+    position: Vec2,
+    direction: Vec2,
+    distance: f32,
+    height: f32,
+) -> bool {
+    let origin = Vec3::new(position.x, height, position.y);
+    let direction = Vec3::new(direction.x, 0.0, direction.y).normalize_or_zero();
+
+    rapier_context
+        .cast_ray(origin, direction, distance, true, bullet::level_filter())
+        .is_none()
+}
+
+/// The closest walkable spot the bot can reach by moving along one axis that lines up
+/// a clear shot at `target`. Free aim doesn't need to line up
+fn aligned_spot(
+    aim_mode: AimMode,
+    position: Vec2,
+    target: Vec2,
+    height: f32,
+    floor_manager: &floor::FloorManager,
+    rapier_context: &RapierContext,
+) -> Option<Vec2> {
+    if aim_mode == AimMode::Free {
+        return None;
+    }
+
+    let mut spots = vec![
+        Vec2::new(target.x, position.y),
+        Vec2::new(position.x, target.y),
+    ];
+
+    if aim_mode == AimMode::EightWay {
+        let offset = target - position;
+        spots.extend([
+            Vec2::new(target.x - offset.y, position.y),
+            Vec2::new(target.x + offset.y, position.y),
+            Vec2::new(position.x, target.y - offset.x),
+            Vec2::new(position.x, target.y + offset.x),
+        ]);
+    }
+
+    spots
+        .into_iter()
+        .filter(|spot| spot.distance(target) >= MIN_LINE_UP_DISTANCE)
+        .filter(|spot| floor_manager.is_walkable(*spot))
+        .filter(|spot| {
+            let offset = target - *spot;
+            has_line_of_sight(
+                rapier_context,
+                *spot,
+                offset.normalize_or_zero(),
+                offset.length(),
+                height,
+            )
+        })
+        .min_by(|a, b| {
+            a.distance_squared(position)
+                .total_cmp(&b.distance_squared(position))
+        })
+}
+
+/// A sidestep out of the way of the first bullet that will pass within `bullet_distance`
+/// of the bot in the next `warning` seconds, toward whichever side stays walkable
+fn find_dodge(
+    entity: Entity,
+    position: Vec2,
+    bullets: &[FlyingBullet],
+    bullet_distance: f32,
+    warning: f32,
+    floor_manager: &floor::FloorManager,
+) -> Option<Vec2> {
+    let (bullet_position, velocity, _) = bullets
+        .iter()
+        .filter(|bullet| bullet.source != entity && bullet.velocity.length_squared() > 0.0)
+        .filter_map(|bullet| {
+            let (bullet_position, velocity) = (bullet.position, bullet.velocity);
+
+            // when the bullet will be closest to the bot, skipping ones that already went by
+            let time = (position - bullet_position).dot(velocity) / velocity.length_squared();
+            if time < 0.0 || time > warning.min(bullet.time_left) {
+                return None;
+            }
+
+            let closest = bullet_position + velocity * time;
+            (closest.distance(position) <= bullet_distance).then_some((
+                bullet_position,
+                velocity,
+                time,
+            ))
+        })
+        .min_by(|a, b| a.2.total_cmp(&b.2))?;
+
+    // stepping to the side of the bullet's path the bot is already on is quickest
+    let sidestep = if velocity.perp_dot(position - bullet_position) < 0.0 {
+        -velocity.perp().normalize()
+    } else {
+        velocity.perp().normalize()
+    };
+
+    [sidestep, -sidestep]
+        .into_iter()
+        .find(|direction| floor_manager.is_walkable(position + *direction * DODGE_DISTANCE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the bullet fired at the aim point gets there when the burro does
+    fn assert_meets(position: Vec2, target: Vec2, velocity: Vec2, bullet_speed: f32) {
+        let aim_point = lead(position, target, velocity, bullet_speed);
+        let time = position.distance(aim_point) / bullet_speed;
+
+        assert!(aim_point.distance(target + velocity * time) < 1e-4);
+    }
+
+    #[test]
+    fn still_target_is_aimed_at() {
+        let target = Vec2::new(10.0, 0.0);
+
+        assert_eq!(lead(Vec2::ZERO, target, Vec2::ZERO, 10.0), target);
+    }
+
+    #[test]
+    fn moving_target_is_led() {
+        assert_meets(Vec2::ZERO, Vec2::new(10.0, 0.0), Vec2::new(0.0, 5.0), 10.0);
+        assert_meets(Vec2::ONE, Vec2::new(-4.0, 6.0), Vec2::new(3.0, -2.0), 12.0);
+    }
+
+    #[test]
+    fn faster_target_running_away_is_aimed_at() {
+        let target = Vec2::new(10.0, 0.0);
+
+        assert_eq!(lead(Vec2::ZERO, target, Vec2::new(20.0, 0.0), 10.0), target);
+    }
+
+    #[test]
+    fn faster_target_coming_closer_is_led() {
+        let target = Vec2::new(10.0, 0.0);
+        let aim_point = lead(Vec2::ZERO, target, Vec2::new(-20.0, 0.0), 10.0);
+
+        // the sooner of the two meetings, before it passes the bot
+        assert!(aim_point.distance(Vec2::new(10.0 / 3.0, 0.0)) < 1e-4);
+    }
+
+    #[test]
+    fn target_as_fast_as_the_bullet() {
+        let target = Vec2::new(10.0, 0.0);
+
+        assert_eq!(lead(Vec2::ZERO, target, Vec2::new(10.0, 0.0), 10.0), target);
+        assert_eq!(
+            lead(Vec2::ZERO, target, Vec2::new(-10.0, 0.0), 10.0),
+            Vec2::new(5.0, 0.0)
+        );
+    }
+}
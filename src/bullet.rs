@@ -1,14 +1,26 @@
-use crate::{assets::GameAssets, audio, burro, cleanup, config, hit, AppState};
+use crate::simulation::{self, SimulationEventExt};
+use crate::{audio, burro, cleanup, config, hit, is_leaving_match, weapon, AppState};
 use bevy::prelude::*;
 use bevy_mod_outline::{OutlineBundle, OutlineVolume};
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
 
 pub struct BulletPlugin;
 impl Plugin for BulletPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<BulletEvent>()
-            .add_systems(OnExit(AppState::InGame), cleanup::<CleanupMarker>)
+        app.add_simulation_event::<BulletEvent>()
             .add_systems(
-                Update,
+                OnExit(AppState::InGame),
+                cleanup::<CleanupMarker>.run_if(is_leaving_match),
+            )
+            .add_systems(
+                OnExit(AppState::Pause),
+                cleanup::<CleanupMarker>.run_if(is_leaving_match),
+            )
+            // the laser from the mecha pick shouldn't carry into the round, replays skip it
+            .add_systems(OnExit(AppState::MechaPicker), cleanup::<CleanupMarker>)
+            .add_systems(
+                FixedUpdate,
                 (
                     handle_bullet_events.run_if(
                         in_state(AppState::InGame).or_else(in_state(AppState::MechaPicker)),
@@ -17,7 +29,8 @@ impl Plugin for BulletPlugin {
                         in_state(AppState::InGame).or_else(in_state(AppState::MechaPicker)),
                     ),
                 )
-                    .chain(),
+                    .chain()
+                    .in_set(simulation::SimulationSet::Bullets),
             );
     }
 }
@@ -25,141 +38,187 @@ impl Plugin for BulletPlugin {
 #[derive(Event)]
 pub struct BulletEvent {
     pub source: Entity,
-    pub speed: f32,
     pub position: Vec3,
     pub direction: Vec3,
-    pub time_to_live: f32,
-    pub bullet_type: BulletType,
+    pub weapon: Handle<weapon::Weapon>,
 }
 
 #[derive(Component)]
 struct CleanupMarker;
 
 #[derive(Component)]
-struct Bullet {
+pub struct Bullet {
     time_to_live: f32,
     time_alive: f32,
     source: Entity,
     speed: f32,
     direction: Vec3,
-    bullet_type: BulletType,
+    weapon: Handle<weapon::Weapon>,
 }
 
-#[derive(Copy, Clone, PartialEq)]
-pub enum BulletType {
-    Candy,
-    Laser,
+impl Bullet {
+    /// The burro that fired it
+    pub fn source(&self) -> Entity {
+        self.source
+    }
+
+    pub fn velocity(&self) -> Vec3 {
+        self.direction * self.speed
+    }
+
+    /// Seconds until it disappears if it doesn't hit anything
+    pub fn time_left(&self) -> f32 {
+        (self.time_to_live - self.time_alive).max(0.0)
+    }
+}
+
+/// What a bullet does when it runs into level geometry
+#[derive(Copy, Clone, PartialEq, Deserialize)]
+pub enum WallCollision {
+    Stop,
+    PassThrough,
+    Ricochet,
 }
 
 fn handle_bullet_events(
     mut commands: Commands,
-    game_assets: Res<GameAssets>,
+    weapons: Res<Assets<weapon::Weapon>>,
+    match_rules: Res<config::MatchRules>,
     mut bullet_reader: EventReader<BulletEvent>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut audio: audio::GameAudio,
 ) {
     for bullet in bullet_reader.iter() {
-        commands
-            .spawn(match bullet.bullet_type {
-                BulletType::Candy => PbrBundle {
-                    mesh: game_assets.candy.mesh.clone(),
-                    material: materials.add(Color::rgb(0.5, 0.5, 0.3).into()),
-                    transform: Transform::from_xyz(
-                        bullet.position.x + bullet.direction.x,
-                        bullet.position.y + 0.5,
-                        bullet.position.z + bullet.direction.z,
-                    ),
-                    ..Default::default()
-                },
-                BulletType::Laser => PbrBundle {
-                    mesh: game_assets.laser.mesh.clone(),
-                    material: materials.add(Color::rgb(0.6, 0.0, 0.0).into()),
-                    transform: {
-                        let mut transform = Transform::from_xyz(
-                            bullet.position.x + bullet.direction.x,
-                            bullet.position.y + 0.5,
-                            bullet.position.z + bullet.direction.z,
-                        );
-                        if bullet.direction.z != 0.0 {
-                            transform.rotate(Quat::from_rotation_y(std::f32::consts::PI / 2.0));
-                        }
-
-                        transform
-                    },
+        let Some(weapon) = weapons.get(&bullet.weapon) else {
+            continue;
+        };
+
+        for direction in weapon.spread(bullet.direction) {
+            let position = Vec3::new(
+                bullet.position.x + direction.x,
+                bullet.position.y + 0.5,
+                bullet.position.z + direction.z,
+            );
+            commands
+                .spawn(PbrBundle {
+                    mesh: weapon.mesh.clone(),
+                    material: materials.add(weapon.color.into()),
+                    transform: Transform::from_translation(position)
+                        .with_rotation(Quat::from_rotation_y((-direction.z).atan2(direction.x))),
                     ..Default::default()
-                },
-            })
-            .with_children(|builder| {
-                if bullet.bullet_type == BulletType::Laser {
-                    builder.spawn(PointLightBundle {
-                        point_light: PointLight {
-                            intensity: 1600.0,
-                            color: Color::RED,
-                            shadows_enabled: false,
+                })
+                .with_children(|builder| {
+                    if let Some(light) = weapon.light {
+                        builder.spawn(PointLightBundle {
+                            point_light: PointLight {
+                                intensity: 1600.0,
+                                color: light,
+                                shadows_enabled: false,
+                                ..default()
+                            },
+                            transform: Transform::from_xyz(0., -0.5, 0.),
                             ..default()
-                        },
-                        transform: Transform::from_xyz(0., -0.5, 0.),
-                        ..default()
-                    });
-                }
-            })
-            .insert(OutlineBundle {
-                outline: OutlineVolume {
-                    visible: true,
-                    width: 2.5,
-                    colour: if bullet.bullet_type == BulletType::Laser {
-                        Color::BLACK // maybe this is better?
-                                     //Color::RED
-                    } else {
-                        Color::BLACK
+                        });
+                    }
+                })
+                .insert(OutlineBundle {
+                    outline: OutlineVolume {
+                        visible: true,
+                        width: 2.5,
+                        colour: Color::BLACK,
                     },
-                },
-                ..default()
-            })
-            .insert(Bullet {
-                source: bullet.source,
-                time_to_live: if bullet.bullet_type == BulletType::Laser {
-                    bullet.time_to_live + 3.0
-                } else {
-                    bullet.time_to_live
-                },
-                time_alive: 0.0,
-                speed: if bullet.bullet_type == BulletType::Laser {
-                    bullet.speed + 1.0
-                } else {
-                    bullet.speed
-                },
-                direction: bullet.direction,
-                bullet_type: bullet.bullet_type,
-            })
-            .insert(CleanupMarker);
-
-        if bullet.bullet_type == BulletType::Laser {
-            audio.play_sfx(&game_assets.laser_sfx);
-        } else {
-            audio.play_sfx(&game_assets.bloop_sfx);
+                    ..default()
+                })
+                .insert(Bullet {
+                    source: bullet.source,
+                    time_to_live: weapon.lifetime * match_rules.bullet_range_scale,
+                    time_alive: 0.0,
+                    speed: weapon.speed,
+                    direction,
+                    weapon: bullet.weapon.clone(),
+                })
+                .insert(simulation::Interpolated::new(position))
+                .insert(CleanupMarker);
         }
+
+        audio.play_sfx(&weapon.sound);
     }
 }
 
+/// Casts only hit the level, its geometry is in GROUP_1 and burros are in GROUP_2
+pub fn level_filter() -> QueryFilter<'static> {
+    QueryFilter::new().groups(CollisionGroups::new(Group::GROUP_3, Group::GROUP_1))
+}
+
 fn handle_bullets(
     mut commands: Commands,
-    time: Res<Time>,
+    fixed_time: Res<FixedTime>,
     mut bullets: Query<(Entity, &mut Bullet, &mut Transform), Without<burro::Burro>>,
     burros: Query<(Entity, &Transform, &burro::Burro), Without<Bullet>>,
     game_config: Res<config::GameConfiguration>,
+    rapier_context: Res<RapierContext>,
+    weapons: Res<Assets<weapon::Weapon>>,
     mut create_hit_event_writer: EventWriter<hit::CreateHitEvent>,
     mut burro_hit_event_writer: EventWriter<burro::BurroHitEvent>,
 ) {
+    let level_filter = level_filter();
+    let delta = simulation::tick_delta(&fixed_time);
+
     'bullets: for (entity, mut bullet, mut transform) in bullets.iter_mut() {
-        transform.translation += bullet.direction * bullet.speed * time.delta_seconds();
-        if bullet.bullet_type == BulletType::Candy {
-            transform.rotate(Quat::from_rotation_y(4.0 * time.delta_seconds()));
-            transform.rotate(Quat::from_rotation_x(2.50 * time.delta_seconds()));
-            transform.rotate(Quat::from_rotation_z(1.50 * time.delta_seconds()));
+        let Some(weapon) = weapons.get(&bullet.weapon) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+
+        let travel_distance = bullet.speed * delta;
+        let wall_collision = weapon.wall_collision;
+        let wall_hit = if wall_collision == WallCollision::PassThrough {
+            None
+        } else {
+            rapier_context.cast_ray_and_get_normal(
+                transform.translation,
+                bullet.direction,
+                travel_distance,
+                true,
+                level_filter,
+            )
+        };
+
+        let wall_hit = wall_hit
+            .map(|(_, intersection)| (intersection.point, intersection.normal, intersection.toi));
+        if let Some((point, _, _)) = wall_hit {
+            create_hit_event_writer.send(hit::CreateHitEvent {
+                position: point,
+                color: weapon.hit_color,
+            });
+        }
+        match travel(
+            wall_collision,
+            transform.translation,
+            bullet.direction,
+            travel_distance,
+            wall_hit,
+        ) {
+            Some((position, direction)) => {
+                if direction != bullet.direction {
+                    transform.rotation = Quat::from_rotation_y((-direction.z).atan2(direction.x));
+                }
+                transform.translation = position;
+                bullet.direction = direction;
+            }
+            None => {
+                commands.entity(entity).despawn_recursive();
+                continue;
+            }
+        }
+
+        if weapon.spin {
+            transform.rotate(Quat::from_rotation_y(4.0 * delta));
+            transform.rotate(Quat::from_rotation_x(2.50 * delta));
+            transform.rotate(Quat::from_rotation_z(1.50 * delta));
         }
 
-        bullet.time_alive += time.delta_seconds();
+        bullet.time_alive += delta;
 
         if bullet.time_alive > bullet.time_to_live {
             // time to die
@@ -184,12 +243,12 @@ fn handle_bullets(
                 commands.entity(entity).despawn_recursive();
                 burro_hit_event_writer.send(burro::BurroHitEvent {
                     entity: burro_entity,
-                    is_laser: bullet.bullet_type == BulletType::Laser,
+                    weapon: bullet.weapon.clone(),
                     velocity: bullet.direction * bullet.speed,
                 });
                 create_hit_event_writer.send(hit::CreateHitEvent {
                     position: burro_transform.translation,
-                    is_candy: bullet.bullet_type == BulletType::Candy,
+                    color: weapon.hit_color,
                 });
 
                 continue 'bullets;
@@ -197,3 +256,106 @@ fn handle_bullets(
         }
     }
 }
+
+/// Moves a bullet `travel_distance` along `direction` given the point, normal and distance
+/// of the first wall in the way. Returns where it ends up and where it's heading after,
+/// or `None` when the wall stops it
+fn travel(
+    wall_collision: WallCollision,
+    position: Vec3,
+    direction: Vec3,
+    travel_distance: f32,
+    wall_hit: Option<(Vec3, Vec3, f32)>,
+) -> Option<(Vec3, Vec3)> {
+    let Some((point, normal, distance)) = wall_hit else {
+        return Some((position + direction * travel_distance, direction));
+    };
+
+    match wall_collision {
+        WallCollision::Stop => None,
+        WallCollision::PassThrough => Some((position + direction * travel_distance, direction)),
+        WallCollision::Ricochet => {
+            // walls are vertical so keep the bounce on the ground plane
+            let normal = Vec3::new(normal.x, 0.0, normal.z).normalize_or_zero();
+            let reflected = (direction - 2.0 * direction.dot(normal) * normal).normalize_or_zero();
+            Some((point + reflected * (travel_distance - distance), reflected))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a wall at x = 1 facing back at a bullet at the origin
+    const WALL_HIT: Option<(Vec3, Vec3, f32)> = Some((Vec3::X, Vec3::NEG_X, 1.0));
+
+    fn assert_close(actual: Vec3, expected: Vec3) {
+        assert!(
+            actual.distance(expected) < 1e-5,
+            "{:?} isn't {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn nothing_in_the_way_keeps_going() {
+        for wall_collision in [
+            WallCollision::Stop,
+            WallCollision::PassThrough,
+            WallCollision::Ricochet,
+        ] {
+            let (position, direction) =
+                travel(wall_collision, Vec3::ZERO, Vec3::X, 2.0, None).unwrap();
+
+            assert_close(position, Vec3::new(2.0, 0.0, 0.0));
+            assert_eq!(direction, Vec3::X);
+        }
+    }
+
+    #[test]
+    fn stop_despawns_at_the_wall() {
+        assert_eq!(
+            travel(WallCollision::Stop, Vec3::ZERO, Vec3::X, 2.0, WALL_HIT),
+            None
+        );
+    }
+
+    #[test]
+    fn pass_through_keeps_going() {
+        let (position, direction) = travel(
+            WallCollision::PassThrough,
+            Vec3::ZERO,
+            Vec3::X,
+            2.0,
+            WALL_HIT,
+        )
+        .unwrap();
+
+        assert_close(position, Vec3::new(2.0, 0.0, 0.0));
+        assert_eq!(direction, Vec3::X);
+    }
+
+    #[test]
+    fn ricochet_bounces_straight_back() {
+        let (position, direction) =
+            travel(WallCollision::Ricochet, Vec3::ZERO, Vec3::X, 1.5, WALL_HIT).unwrap();
+
+        assert_close(direction, Vec3::NEG_X);
+        assert_close(position, Vec3::new(0.5, 0.0, 0.0));
+    }
+
+    #[test]
+    fn ricochet_reflects_off_the_wall() {
+        let incoming = Vec3::new(1.0, 0.0, 1.0).normalize();
+        // a slanted normal still bounces along the ground
+        let wall_hit = Some((Vec3::new(1.0, 0.0, 1.0), Vec3::new(-1.0, 0.5, 0.0), 2.0));
+        let (position, direction) =
+            travel(WallCollision::Ricochet, Vec3::ZERO, incoming, 3.0, wall_hit).unwrap();
+
+        let reflected = Vec3::new(-1.0, 0.0, 1.0).normalize();
+        assert_close(direction, reflected);
+        assert_close(position, Vec3::new(1.0, 0.0, 1.0) + reflected);
+    }
+}
@@ -1,4 +1,5 @@
-use crate::{assets, audio, config, game_state, smoke, AppState, IngameState};
+use crate::simulation::{self, SimulationEventExt};
+use crate::{assets, audio, config, game_state, rng, smoke, weapon, AppState, IngameState};
 use bevy::prelude::*;
 use bevy_toon_shader::ToonShaderMaterial;
 use rand::Rng;
@@ -7,18 +8,20 @@ pub struct BurroPlugin;
 impl Plugin for BurroPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            Update,
+            FixedUpdate,
             (
                 handle_burros,
                 handle_burro_death_events.run_if(|g: Res<game_state::GameState>| !g.is_game_over()),
                 handle_burro_hit,
+                handle_fallen_burros,
             )
                 .chain()
+                .in_set(simulation::SimulationSet::Burros)
                 .run_if(in_state(AppState::InGame)),
         )
         .add_systems(
             Update,
-            (handle_fallen_burros, handle_burro_flash_events).run_if(in_state(AppState::InGame)),
+            handle_burro_flash_events.run_if(in_state(AppState::InGame)),
         )
         .add_systems(
             Update,
@@ -26,8 +29,8 @@ impl Plugin for BurroPlugin {
                 .run_if(in_state(AppState::InGame).or_else(in_state(AppState::MechaPicker))),
         )
         .add_event::<BurroFlashEvent>()
-        .add_event::<BurroHitEvent>()
-        .add_event::<BurroDeathEvent>();
+        .add_simulation_event::<BurroHitEvent>()
+        .add_simulation_event::<BurroDeathEvent>();
     }
 }
 
@@ -35,7 +38,7 @@ impl Plugin for BurroPlugin {
 pub struct BurroHitEvent {
     pub entity: Entity,
     pub velocity: Vec3,
-    pub is_laser: bool,
+    pub weapon: Handle<weapon::Weapon>,
 }
 
 #[derive(Event)]
@@ -54,15 +57,12 @@ pub struct BurroFlashEvent {
 pub struct Burro {
     pub selected_burro: usize,
     pub health: usize,
-    pub bullet_speed: f32,
-    pub bullet_time_alive: f32,
     pub fire_cooldown: f32,
     pub invulnerability_cooldown: f32,
     pub is_visible: bool,
     pub is_mechaburro: bool,
     pub is_down: bool,
     pub down_cooldown: f32,
-    pub speed: f32,
     pub friction: f32,
     pub velocity: Vec3,
     pub random: f32,
@@ -70,19 +70,14 @@ pub struct Burro {
 }
 
 impl Burro {
-    pub fn new(selected_burro: usize) -> Self {
-        let mut rng = rand::thread_rng();
-
+    pub fn new(selected_burro: usize, health: usize, rng: &mut impl Rng) -> Self {
         Burro {
             selected_burro,
-            health: 3,
-            bullet_speed: 12.0,
-            bullet_time_alive: 1.0,
+            health,
             fire_cooldown: 0.0,
             invulnerability_cooldown: 0.0,
             is_visible: true,
             is_mechaburro: false,
-            speed: 60.0,
             friction: 0.0005,
             velocity: Vec3::ZERO,
             random: rng.gen_range(0.5..1.0),
@@ -96,8 +91,8 @@ impl Burro {
         self.fire_cooldown <= 0.0 && !self.is_invulnerable()
     }
 
-    pub fn fire(&mut self) {
-        self.fire_cooldown = 0.4;
+    pub fn fire(&mut self, cooldown: f32) {
+        self.fire_cooldown = cooldown;
     }
 
     pub fn hit(&mut self, down_cooldown: f32) {
@@ -147,26 +142,29 @@ fn handle_burro_hit(
     mut burro_hit_event_reader: EventReader<BurroHitEvent>,
     mut burros: Query<(Entity, &mut Burro, &mut Transform)>,
     mut audio: audio::GameAudio,
-    game_config: Res<config::GameConfiguration>,
+    match_rules: Res<config::MatchRules>,
     game_assets: Res<assets::GameAssets>,
+    weapons: Res<Assets<weapon::Weapon>>,
+    mut game_rng: ResMut<rng::GameRng>,
 ) {
     for event in burro_hit_event_reader.iter() {
-        let mut rng = rand::thread_rng();
         if let Ok((entity, mut burro, mut transform)) = burros.get_mut(event.entity) {
-            burro.hit(game_config.down_cooldown);
+            burro.hit(match_rules.down_cooldown);
 
-            let random_z = rng.gen_range(0.0..std::f32::consts::TAU);
+            let random_z = game_rng.cosmetic.gen_range(0.0..std::f32::consts::TAU);
             transform.rotation = Quat::from_rotation_x((3.0 * std::f32::consts::PI) / 2.0);
             transform.rotation *= Quat::from_rotation_z(random_z);
 
-            burro.velocity += event.velocity * 4.0;
+            let Some(weapon) = weapons.get(&event.weapon) else {
+                continue;
+            };
+
+            burro.velocity += event.velocity * weapon.knockback;
 
-            if event.is_laser {
-                audio.play_sfx(&game_assets.laser_hit_sfx);
+            audio.play_sfx(&weapon.hit_sound);
+            if weapon.smoke_on_hit {
                 audio.play_sfx(&game_assets.smoke_sfx);
                 commands.entity(entity).insert(smoke::Smoker::default());
-            } else {
-                audio.play_sfx(&game_assets.candy_hit_sfx);
             }
         }
     }
@@ -174,11 +172,13 @@ fn handle_burro_hit(
 
 fn handle_burros(
     time: Res<Time>,
+    fixed_time: Res<FixedTime>,
     mut burros: Query<(Entity, &mut Burro)>,
     mut burro_death_event_writer: EventWriter<BurroDeathEvent>,
     mut flash_event_writer: EventWriter<BurroFlashEvent>,
     game_state: Res<game_state::GameState>,
 ) {
+    let delta = simulation::tick_delta(&fixed_time);
     for (entity, mut burro) in burros.iter_mut() {
         let current_sin = (time.elapsed_seconds() * (1.0 + burro.random) * 8.0).sin();
 
@@ -196,13 +196,13 @@ fn handle_burros(
             2.0 * game_state.difficulty
         } else {
             1.0
-        } * time.delta_seconds();
+        } * delta;
 
         burro.fire_cooldown = burro.fire_cooldown.clamp(-10.0, 3.0);
 
         // handling invulnerability
         let is_invulnerable = burro.is_invulnerable();
-        burro.invulnerability_cooldown -= time.delta_seconds();
+        burro.invulnerability_cooldown -= delta;
         burro.invulnerability_cooldown = burro.invulnerability_cooldown.clamp(-10.0, 3.0);
 
         if is_invulnerable && !burro.is_invulnerable() {
@@ -251,21 +251,21 @@ fn handle_burro_death_events(
 fn handle_fallen_burros(
     mut commands: Commands,
     mut burros: Query<(Entity, &mut Burro, &mut Transform)>,
-    time: Res<Time>,
-    game_config: Res<config::GameConfiguration>,
+    fixed_time: Res<FixedTime>,
+    match_rules: Res<config::MatchRules>,
 ) {
     for (entity, mut burro, mut transform) in burros.iter_mut() {
         if !burro.is_down {
             continue;
         }
 
-        burro.down_cooldown -= time.delta_seconds();
+        burro.down_cooldown -= simulation::tick_delta(&fixed_time);
         burro.down_cooldown = burro.down_cooldown.clamp(-3.0, 10.0);
         if burro.down_cooldown < 0.0 {
             transform.rotation = Quat::from_rotation_y(std::f32::consts::PI * 2.0);
             burro.down_cooldown = 0.0;
             burro.is_down = false;
-            burro.invulnerability_cooldown = game_config.invulnerability_cooldown;
+            burro.invulnerability_cooldown = match_rules.invulnerability_cooldown;
             commands.entity(entity).remove::<smoke::Smoker>();
         }
     }
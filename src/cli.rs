@@ -0,0 +1,444 @@
+use crate::loading::command_ext::StateSetter;
+use crate::{
+    asset_loading, assets, bot, config, game_state,
+    headless::validate,
+    input,
+    menu::{character_select, settings::state::SettingsMenuState, title_screen},
+    rng, AppState,
+};
+use bevy::{app::AppExit, ecs::system::Command, prelude::*};
+use std::path::PathBuf;
+
+const USAGE: &str = "\
+Usage: quien_es_el_mechaburro [OPTIONS]
+
+Match options, passing any of these skips the menus and starts a match:
+    --level <N>            level to start on, counting from 0 in assets/config/levels.ron
+    --humans <N>           number of human players, player 1 uses the keyboard
+    --bots <N>             number of bots
+    --bot-profiles <A,..>  profile for each bot from assets/bots, the rest use the
+                           settings menu's profile for their slot
+    --difficulty <N>       how much stronger the mechaburro is, 0.5 is the lowest
+    --burros <A,B,..>      burros for the human players, by name or index
+    --seed <N>             seed for the match, the same seed plays out the same way
+
+Other options:
+    --skip-intro           go straight to the title screen
+    --skip-cutscenes       pick the mechaburro without the animation
+    --replay <FILE>        play back a recorded round
+    --help                 print this message
+
+Simulation:
+    --simulate <N>         play N bot only matches on each level without a window and print
+                           how they went, --level, --bots, --bot-profiles, --difficulty and
+                           --seed apply too
+
+Level checks:
+    --check-level <FILE>   check a level glb has what a match needs, prints what it found
+                           and exits with an error code if anything would break
+    --check-stage <FILE>   the same for the winner's stage";
+
+// same as the lowest unfair advantage in the settings menu
+pub const MIN_DIFFICULTY: f32 = 0.5;
+
+/// Options passed on the command line, read once at startup
+#[derive(Default, Resource)]
+pub struct LaunchOptions {
+    pub level: Option<usize>,
+    pub humans: Option<usize>,
+    pub bots: Option<usize>,
+    pub bot_profiles: Vec<String>,
+    pub difficulty: Option<f32>,
+    pub burros: Vec<String>,
+    pub seed: Option<u64>,
+    pub skip_intro: bool,
+    pub skip_cutscenes: bool,
+    pub replay: Option<PathBuf>,
+    pub simulate: Option<usize>,
+    pub check_level: Option<(PathBuf, validate::LevelKind)>,
+}
+
+impl LaunchOptions {
+    /// Parses the process arguments, printing the usage and exiting if they're invalid.
+    /// `--level` is checked separately by [`Self::exit_if_level_missing`] since the level
+    /// checks run without the level manifest.
+    pub fn from_env() -> Self {
+        match Self::parse(std::env::args().skip(1)) {
+            Ok(Some(options)) => options,
+            Ok(None) => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            Err(error) => exit_with_usage(&error),
+        }
+    }
+
+    /// Prints the usage and exits if `--level` is past the levels that were found
+    pub fn exit_if_level_missing(&self, number_of_levels: usize) {
+        if let Err(error) = self.check_level_index(number_of_levels) {
+            exit_with_usage(&error);
+        }
+    }
+
+    fn check_level_index(&self, number_of_levels: usize) -> Result<(), String> {
+        match self.level {
+            Some(level) if level >= number_of_levels => {
+                Err(format!("--level must be less than {}", number_of_levels))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns `None` when help was requested
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut options = LaunchOptions::default();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}", arg))
+            };
+
+            match arg.as_str() {
+                "--level" => options.level = Some(parse_value(&arg, value()?)?),
+                "--humans" => options.humans = Some(parse_value(&arg, value()?)?),
+                "--bots" => options.bots = Some(parse_value(&arg, value()?)?),
+                "--bot-profiles" => {
+                    options.bot_profiles = value()?
+                        .split(',')
+                        .map(|profile| profile.trim().to_lowercase())
+                        .filter(|profile| !profile.is_empty())
+                        .collect();
+                }
+                "--difficulty" => options.difficulty = Some(parse_value(&arg, value()?)?),
+                "--burros" => {
+                    options.burros = value()?
+                        .split(',')
+                        .map(|burro| burro.trim().to_string())
+                        .filter(|burro| !burro.is_empty())
+                        .collect();
+                }
+                "--seed" => options.seed = Some(parse_value(&arg, value()?)?),
+                "--skip-intro" => options.skip_intro = true,
+                "--skip-cutscenes" => options.skip_cutscenes = true,
+                "--replay" => options.replay = Some(PathBuf::from(value()?)),
+                "--simulate" => options.simulate = Some(parse_value(&arg, value()?)?),
+                "--check-level" => {
+                    options.check_level =
+                        Some((PathBuf::from(value()?), validate::LevelKind::Level));
+                }
+                "--check-stage" => {
+                    options.check_level =
+                        Some((PathBuf::from(value()?), validate::LevelKind::Stage));
+                }
+                "--help" | "-h" => return Ok(None),
+                _ => return Err(format!("Unknown argument {}", arg)),
+            }
+        }
+
+        if options.simulate == Some(0) {
+            return Err("--simulate needs at least one match".to_string());
+        }
+
+        Ok(Some(options))
+    }
+
+    /// A profile for each of the bots, the ones without one use the profile the settings
+    /// menu has for their slot
+    pub fn bot_profiles(&self, bots: usize, settings_menu: &SettingsMenuState) -> Vec<String> {
+        (0..bots)
+            .map(|i| {
+                self.bot_profiles
+                    .get(i)
+                    .cloned()
+                    .unwrap_or_else(|| settings_menu.slot_profile(i).to_string())
+            })
+            .collect()
+    }
+
+    /// Errors on the first `--bot-profiles` name that isn't one of `profiles`
+    pub fn check_bot_profiles(&self, profiles: &[String]) -> Result<(), String> {
+        match self
+            .bot_profiles
+            .iter()
+            .find(|name| !profiles.contains(name))
+        {
+            Some(name) => {
+                let mut profiles = profiles.to_vec();
+                profiles.sort();
+                Err(format!(
+                    "No bot profile named {}, pick from {}",
+                    name,
+                    profiles.join(", ")
+                ))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Whether any match option was given, in which case the menus are skipped
+    pub fn quick_launch(&self) -> bool {
+        self.level.is_some()
+            || self.humans.is_some()
+            || self.bots.is_some()
+            || !self.bot_profiles.is_empty()
+            || self.difficulty.is_some()
+            || !self.burros.is_empty()
+            || self.seed.is_some()
+    }
+}
+
+fn exit_with_usage(error: &str) -> ! {
+    eprintln!("{}\n\n{}", error, USAGE);
+    std::process::exit(1);
+}
+
+fn parse_value<T: std::str::FromStr>(arg: &str, value: String) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value for {}: {}", arg, value))
+}
+
+pub fn skip_cutscenes(launch_options: Res<LaunchOptions>) -> bool {
+    launch_options.skip_cutscenes
+}
+
+/// Sets up the game state from the launch options and loads straight into a match,
+/// anything left out falls back to the saved settings
+pub struct QuickLaunch;
+impl Command for QuickLaunch {
+    fn apply(self, world: &mut World) {
+        // the match needs the burro textures and the menu fonts and sounds
+        character_select::loader::CharacterSelectLoader.apply(world);
+        title_screen::loader::TitleScreenLoader.apply(world);
+
+        // there's no match without burros, loading shows what's missing instead
+        if world
+            .resource::<assets::GameAssets>()
+            .burro_assets
+            .is_empty()
+        {
+            StateSetter(AppState::CharacterSelect).apply(world);
+            return;
+        }
+
+        // the profiles themselves load with the round, so check the names against the folder
+        let profiles = asset_loading::folder_assets("bots", bot::BOT_PROFILE_EXTENSION)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        if let Err(error) = world
+            .resource::<LaunchOptions>()
+            .check_bot_profiles(&profiles)
+        {
+            error!("{}", error);
+            world.resource_mut::<Events<AppExit>>().send(AppExit);
+            return;
+        }
+
+        // a match needs somebody to play against and each burro has its own texture
+        let number_of_burros = world.resource::<assets::GameAssets>().burro_assets.len();
+        if number_of_burros < 2 {
+            error!(
+                "A match needs at least 2 burros but there's only {} in textures/burros",
+                number_of_burros
+            );
+            world.resource_mut::<Events<AppExit>>().send(AppExit);
+            return;
+        }
+
+        let launch_options = world.resource::<LaunchOptions>();
+        let settings_menu = world.resource::<SettingsMenuState>();
+        let match_rules = world.resource::<config::MatchRules>();
+        let burro_assets = &world.resource::<assets::GameAssets>().burro_assets;
+
+        let max_players = (config::MAX_NUMBER_OF_PLAYERS as usize).min(burro_assets.len());
+        let humans = launch_options.humans.unwrap_or(1).clamp(1, max_players);
+        let min_bots = if humans == 1 { 1 } else { 0 };
+        let bots = launch_options
+            .bots
+            .unwrap_or(settings_menu.number_of_bots.max(0) as usize)
+            .clamp(min_bots, max_players - humans);
+        let difficulty = launch_options
+            .difficulty
+            .unwrap_or(settings_menu.unfair_advantage as f32 + MIN_DIFFICULTY)
+            .max(MIN_DIFFICULTY);
+        let level = launch_options.level.unwrap_or(0);
+
+        let mut claimed_burros = vec![];
+        for name in launch_options.burros.iter().take(humans) {
+            match find_burro(burro_assets, name) {
+                Some(burro) if !claimed_burros.contains(&burro) => claimed_burros.push(burro),
+                Some(_) => warn!("Burro {} was picked more than once, ignoring it", name),
+                None => warn!("No burro named {}, picking one instead", name),
+            }
+        }
+        let free_burros = (0..burro_assets.len())
+            .filter(|x| !claimed_burros.contains(x))
+            .collect::<Vec<_>>();
+        let missing = humans - claimed_burros.len();
+        claimed_burros.extend(free_burros.into_iter().take(missing));
+
+        let players = claimed_burros
+            .into_iter()
+            .enumerate()
+            .map(|(player, selected_burro)| game_state::BurroState {
+                player,
+                selected_burro,
+                outline_color: character_select::OUTLINE_COLORS
+                    [player % character_select::OUTLINE_COLORS.len()],
+                score: 0,
+                is_bot: false,
+                bot_profile: None,
+                hearts: vec![],
+                input_device: if player == 0 {
+                    input::InputDevice::Keyboard
+                } else {
+                    input::InputDevice::Gamepad(Gamepad { id: player - 1 })
+                },
+            })
+            .collect::<Vec<_>>();
+
+        // this runs once game.config.ron has been read so a seed set there applies too
+        let seed = launch_options
+            .seed
+            .or(world.resource::<config::GameConfiguration>().seed);
+        let mut game_rng = rng::GameRng::default();
+        game_rng.start_match(seed);
+
+        // starting partway through still plays the configured number of rounds
+        let mut game_state = game_state::GameState::initialize(
+            players,
+            bots,
+            &launch_options.bot_profiles(bots, settings_menu),
+            difficulty,
+            level + match_rules.rounds,
+            burro_assets,
+            &mut game_rng.gameplay,
+        );
+        game_state.current_level = level;
+        world.insert_resource(game_state);
+        world.insert_resource(game_rng);
+
+        StateSetter(AppState::LoadInGame).apply(world);
+    }
+}
+
+/// Matches a burro by its index or its texture name, ignoring case
+fn find_burro(burro_assets: &[assets::BurroAsset], name: &str) -> Option<usize> {
+    if let Ok(index) = name.parse::<usize>() {
+        return (index < burro_assets.len()).then_some(index);
+    }
+
+    let name = name.replace('_', " ").to_uppercase();
+    burro_assets.iter().position(|burro| burro.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<LaunchOptions>, String> {
+        LaunchOptions::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_match_options() {
+        let options = parse(&[
+            "--level",
+            "2",
+            "--bots",
+            "3",
+            "--bot-profiles",
+            "Easy, hard,",
+            "--burros",
+            "pinata,1",
+            "--seed",
+            "7",
+        ])
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(options.level, Some(2));
+        assert_eq!(options.bots, Some(3));
+        assert_eq!(options.bot_profiles, vec!["easy", "hard"]);
+        assert_eq!(options.burros, vec!["pinata", "1"]);
+        assert_eq!(options.seed, Some(7));
+        assert!(options.quick_launch());
+    }
+
+    #[test]
+    fn no_options_skip_nothing() {
+        let options = parse(&[]).unwrap().unwrap();
+
+        assert!(!options.quick_launch());
+    }
+
+    #[test]
+    fn help_returns_none() {
+        assert!(parse(&["--level", "1", "--help"]).unwrap().is_none());
+    }
+
+    #[test]
+    fn bad_level_is_an_error() {
+        assert!(parse(&["--level", "first"]).is_err());
+        assert!(parse(&["--level", "-1"]).is_err());
+    }
+
+    #[test]
+    fn missing_value_is_an_error() {
+        assert_eq!(
+            parse(&["--seed"]).err(),
+            Some("Missing value for --seed".to_string())
+        );
+    }
+
+    #[test]
+    fn unknown_argument_is_an_error() {
+        assert_eq!(
+            parse(&["--levle", "1"]).err(),
+            Some("Unknown argument --levle".to_string())
+        );
+    }
+
+    #[test]
+    fn simulating_no_matches_is_an_error() {
+        assert!(parse(&["--simulate", "0"]).is_err());
+        assert_eq!(
+            parse(&["--simulate", "2"]).unwrap().unwrap().simulate,
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn level_past_the_manifest_is_an_error() {
+        let options = parse(&["--level", "3"]).unwrap().unwrap();
+
+        assert!(options.check_level_index(3).is_err());
+        assert!(options.check_level_index(4).is_ok());
+        assert!(LaunchOptions::default().check_level_index(0).is_ok());
+    }
+
+    #[test]
+    fn check_stage_is_not_a_quick_launch() {
+        let options = parse(&["--check-stage", "stage.glb"]).unwrap().unwrap();
+
+        assert!(matches!(
+            options.check_level,
+            Some((_, validate::LevelKind::Stage))
+        ));
+        assert!(!options.quick_launch());
+    }
+
+    #[test]
+    fn unknown_bot_profile_is_an_error() {
+        let options = parse(&["--bot-profiles", "normal,expert"])
+            .unwrap()
+            .unwrap();
+        let profiles = ["normal".to_string(), "hard".to_string()];
+
+        assert_eq!(
+            options.check_bot_profiles(&profiles).err(),
+            Some("No bot profile named expert, pick from hard, normal".to_string())
+        );
+    }
+}
@@ -1,15 +1,67 @@
-use bevy::prelude::*;
+use crate::{asset_loading, player, replay, weapon};
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadState, LoadedAsset},
+    prelude::*,
+    reflect::{Struct, TypeUuid},
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 pub const MAX_NUMBER_OF_PLAYERS: isize = 8;
-pub const NUMBER_OF_LEVELS: usize = 7;
+// a round on each of the levels the game ships with
+const DEFAULT_ROUNDS: usize = 7;
 
+pub const CONFIGURATION_PATH: &str = "config/game.config.ron";
+
+pub struct ConfigPlugin;
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<GameConfiguration>()
+            .init_asset_loader::<GameConfigurationLoader>()
+            .init_resource::<ConfigOverrides>()
+            .add_systems(Startup, load_configuration)
+            .add_systems(
+                Update,
+                (
+                    apply_configuration_changes,
+                    track_config_overrides.run_if(
+                        resource_changed::<GameConfiguration>().and_then(not(replay::is_replaying)),
+                    ),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Keeps the configuration file loaded so edits to it are picked up
 #[derive(Resource)]
+pub struct GameConfigurationHandle {
+    handle: Handle<GameConfiguration>,
+    /// What the file held the last time it was read, edits are logged against it
+    last_read: Option<GameConfiguration>,
+}
+
+impl GameConfigurationHandle {
+    /// Whether the file has been applied, or couldn't be loaded and never will be
+    pub fn is_read(&self, asset_server: &AssetServer) -> bool {
+        self.last_read.is_some() || asset_server.get_load_state(&self.handle) == LoadState::Failed
+    }
+}
+
+/// Tuning values the player changed away from what the configuration file holds, by
+/// field name. They're kept in the settings file and layered over the file when it's read
+#[derive(Resource, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ConfigOverrides(pub BTreeMap<String, ron::Value>);
+
+#[derive(Resource, Clone, Serialize, Deserialize, Reflect, TypeUuid)]
+#[uuid = "9f5c2d1a-6a3e-4c47-b0f2-51e3c8a7d214"]
+#[serde(default)]
 pub struct GameConfiguration {
     pub bullet_distance: f32,
     pub burro_speed: f32,
     pub follow_burros: bool,
-    pub invulnerability_cooldown: f32,
-    pub down_cooldown: f32,
     pub max_camera_yaw: f32,
     pub max_camera_pitch: f32,
     pub max_camera_roll: f32,
@@ -23,16 +75,22 @@ pub struct GameConfiguration {
     pub hit_max_spread_y: f32,
     pub hit_min_spread_z: f32,
     pub hit_max_spread_z: f32,
+    pub burro_weapon: String,
+    pub mechaburro_weapon: String,
+    /// How far apart burros start a round, spawn points closer than this are passed over
+    pub spawn_spacing: f32,
+    /// Hands the spawn points out in turn each round instead of shuffling them
+    pub rotate_spawn_points: bool,
+    /// Seed for every match, a new one is picked each match when unset
+    pub seed: Option<u64>,
 }
 
 impl Default for GameConfiguration {
     fn default() -> Self {
         GameConfiguration {
             bullet_distance: 1.0,
-            burro_speed: 40.0,
+            burro_speed: 60.0,
             follow_burros: true,
-            invulnerability_cooldown: 1.0,
-            down_cooldown: 1.5,
             max_camera_yaw: 1.0,
             max_camera_pitch: 1.0,
             max_camera_roll: 1.0,
@@ -46,6 +104,233 @@ impl Default for GameConfiguration {
             hit_max_spread_y: 1.0,
             hit_min_spread_z: 0.0,
             hit_max_spread_z: 1.0,
+            burro_weapon: "candy".into(),
+            mechaburro_weapon: "laser".into(),
+            spawn_spacing: 3.0,
+            rotate_spawn_points: false,
+            seed: None,
+        }
+    }
+}
+
+impl GameConfiguration {
+    pub fn weapon_name(&self, is_mechaburro: bool) -> &str {
+        if is_mechaburro {
+            &self.mechaburro_weapon
+        } else {
+            &self.burro_weapon
         }
     }
+
+    /// The weapons picked for the burros and the mechaburro that aren't in `assets/weapons`
+    fn missing_weapons(&self) -> Vec<&str> {
+        let weapons = asset_loading::folder_assets("weapons", weapon::WEAPON_EXTENSION);
+        [self.burro_weapon.as_str(), self.mechaburro_weapon.as_str()]
+            .into_iter()
+            .filter(|name| !weapons.iter().any(|(weapon, _)| weapon == name))
+            .collect()
+    }
+
+    /// Each field that differs from `other` with its name and "old -> new"
+    fn changes(&self, other: &GameConfiguration) -> Vec<(String, String)> {
+        let mut changes = vec![];
+        for (i, field) in self.iter_fields().enumerate() {
+            let Some(other_field) = other.field_at(i) else {
+                continue;
+            };
+
+            if field.reflect_partial_eq(other_field) != Some(true) {
+                changes.push((
+                    self.name_at(i).unwrap_or_default().to_string(),
+                    format!("{:?} -> {:?}", field, other_field),
+                ));
+            }
+        }
+
+        changes
+    }
+
+    /// The fields by name, in the form they're written to a ron file
+    fn fields(&self) -> BTreeMap<String, ron::Value> {
+        let value = ron::to_string(self)
+            .ok()
+            .and_then(|contents| ron::from_str::<ron::Value>(&contents).ok());
+        let Some(ron::Value::Map(map)) = value else {
+            return BTreeMap::new();
+        };
+
+        map.iter()
+            .filter_map(|(name, value)| match name {
+                ron::Value::String(name) => Some((name.clone(), value.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn from_fields(fields: BTreeMap<String, ron::Value>) -> Result<Self, ron::Error> {
+        ron::Value::Map(
+            fields
+                .into_iter()
+                .map(|(name, value)| (ron::Value::String(name), value))
+                .collect(),
+        )
+        .into_rust()
+    }
+
+    /// The fields that differ from `base`, with the values they have here
+    fn overrides(&self, base: &GameConfiguration) -> ConfigOverrides {
+        let base = base.fields();
+        ConfigOverrides(
+            self.fields()
+                .into_iter()
+                .filter(|(name, value)| base.get(name) != Some(value))
+                .collect(),
+        )
+    }
+
+    /// This configuration with the overridden fields swapped in, overrides that don't
+    /// fit a field are skipped
+    fn with_overrides(&self, overrides: &ConfigOverrides) -> GameConfiguration {
+        let mut configuration = self.clone();
+        for (name, value) in &overrides.0 {
+            let mut fields = configuration.fields();
+            if !fields.contains_key(name) {
+                warn!("There's no tuning value named {}, ignoring it", name);
+                continue;
+            }
+
+            fields.insert(name.clone(), value.clone());
+            match GameConfiguration::from_fields(fields) {
+                Ok(overridden) => configuration = overridden,
+                Err(error) => warn!("Ignoring the saved {}: {}", name, error),
+            }
+        }
+
+        configuration
+    }
+}
+
+/// Rules for a match, chosen on the settings menu before it starts
+#[derive(Resource, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MatchRules {
+    pub rounds: usize,
+    pub starting_hearts: usize,
+    pub fire_cooldown_scale: f32,
+    pub down_cooldown: f32,
+    pub invulnerability_cooldown: f32,
+    pub bullet_range_scale: f32,
+    pub aim_mode: player::AimMode,
+}
+
+impl Default for MatchRules {
+    fn default() -> Self {
+        MatchRules {
+            rounds: DEFAULT_ROUNDS,
+            starting_hearts: 3,
+            fire_cooldown_scale: 1.0,
+            down_cooldown: 1.5,
+            invulnerability_cooldown: 1.0,
+            bullet_range_scale: 1.0,
+            aim_mode: player::AimMode::default(),
+        }
+    }
+}
+
+fn load_configuration(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(GameConfigurationHandle {
+        handle: asset_server.load(CONFIGURATION_PATH),
+        last_read: None,
+    });
+}
+
+fn apply_configuration_changes(
+    mut asset_events: EventReader<AssetEvent<GameConfiguration>>,
+    configurations: Res<Assets<GameConfiguration>>,
+    mut configuration_handle: ResMut<GameConfigurationHandle>,
+    mut config_overrides: ResMut<ConfigOverrides>,
+    mut game_config: ResMut<GameConfiguration>,
+    playback: Option<Res<replay::ReplayPlayback>>,
+) {
+    for event in asset_events.iter() {
+        let (AssetEvent::Created { handle } | AssetEvent::Modified { handle }) = event else {
+            continue;
+        };
+        if *handle != configuration_handle.handle {
+            continue;
+        }
+        let Some(configuration) = configurations.get(handle) else {
+            continue;
+        };
+
+        // the first read is applied with the saved overrides on top, after that only
+        // edits to the file are and an edit wins over a saved value for the same field
+        match &configuration_handle.last_read {
+            Some(last_read) => {
+                let changes = last_read.changes(configuration);
+                if changes.is_empty() {
+                    continue;
+                }
+
+                config_overrides
+                    .0
+                    .retain(|name, _| !changes.iter().any(|(changed, _)| changed == name));
+                let changes = changes
+                    .iter()
+                    .map(|(name, change)| format!("{}: {}", name, change))
+                    .collect::<Vec<_>>();
+                info!("{} changed: {}", CONFIGURATION_PATH, changes.join(", "));
+            }
+            None if !config_overrides.0.is_empty() => {
+                let names = config_overrides.0.keys().cloned().collect::<Vec<_>>();
+                info!("Using the saved values for: {}", names.join(", "));
+            }
+            None => {}
+        }
+
+        for weapon in configuration.missing_weapons() {
+            error!(
+                "{}: there's no weapon named {} in assets/weapons, it can't be fired",
+                CONFIGURATION_PATH, weapon
+            );
+        }
+
+        // a replay plays with the configuration it was recorded with
+        if playback.is_none() {
+            *game_config = configuration.with_overrides(&config_overrides);
+        }
+        configuration_handle.last_read = Some(configuration.clone());
+    }
+}
+
+/// Keeps the overrides in step with tuning values changed while the game runs,
+/// so they're saved with the other settings
+fn track_config_overrides(
+    configuration_handle: Res<GameConfigurationHandle>,
+    game_config: Res<GameConfiguration>,
+    mut config_overrides: ResMut<ConfigOverrides>,
+) {
+    if let Some(last_read) = &configuration_handle.last_read {
+        config_overrides.set_if_neq(game_config.overrides(last_read));
+    }
+}
+
+#[derive(Default)]
+struct GameConfigurationLoader;
+impl AssetLoader for GameConfigurationLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let configuration: GameConfiguration = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(configuration));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["config.ron"]
+    }
 }
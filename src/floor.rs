@@ -1,6 +1,17 @@
 use bevy::prelude::*;
 use bevy::render::primitives::Aabb;
-use rand::seq::SliceRandom;
+use rand::{seq::SliceRandom, Rng};
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+// size of a navigation cell on the ground, small enough to fit through the gaps between walls
+const NAV_CELL_SIZE: f32 = 0.5;
+// how far a burro's center has to stay from a wall, its collider is a ball this big
+const WALL_CLEARANCE: f32 = 1.0;
+// path costs are whole numbers so the open set can be ordered, a diagonal step costs about √2
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+// random spots that land on a blocked cell are retried this many times
+const RANDOM_SPOT_ATTEMPTS: usize = 10;
 
 pub struct FloorPlugin;
 impl Plugin for FloorPlugin {
@@ -11,52 +22,469 @@ impl Plugin for FloorPlugin {
 
 #[derive(Default, Resource)]
 pub struct FloorManager {
-    floors: Vec<Floor>,
+    floors: Vec<Bounds>,
+    walls: Vec<Bounds>,
+    nav_grid: Option<NavGrid>,
 }
 
-struct Floor {
+struct Bounds {
     min: Vec3,
     max: Vec3,
 }
 
+impl Bounds {
+    fn new(global_transform: &GlobalTransform, aabb: &Aabb) -> Self {
+        let matrix = global_transform.compute_matrix();
+        let (aabb_min, aabb_max): (Vec3, Vec3) = (aabb.min().into(), aabb.max().into());
+
+        // once the box is rotated any of its corners can end up furthest out
+        let mut min = Vec3::MAX;
+        let mut max = Vec3::MIN;
+        for corner in 0..8 {
+            let mask = BVec3::new(corner & 1 != 0, corner & 2 != 0, corner & 4 != 0);
+            let point = matrix.transform_point3(Vec3::select(mask, aabb_max, aabb_min));
+            min = min.min(point);
+            max = max.max(point);
+        }
+
+        Bounds { min, max }
+    }
+
+    fn contains(&self, x: f32, z: f32) -> bool {
+        x <= self.max.x && x >= self.min.x && z <= self.max.z && z >= self.min.z
+    }
+}
+
+/// Which parts of the floors a burro can stand on, in square cells on the ground plane
+struct NavGrid {
+    origin: Vec2,
+    width: usize,
+    height: usize,
+    walkable: Vec<bool>,
+}
+
+impl NavGrid {
+    fn build(floors: &[Bounds], walls: &[Bounds]) -> Self {
+        let min = floors.iter().fold(Vec2::splat(f32::MAX), |min, floor| {
+            min.min(Vec2::new(floor.min.x, floor.min.z))
+        });
+        let max = floors.iter().fold(Vec2::splat(f32::MIN), |max, floor| {
+            max.max(Vec2::new(floor.max.x, floor.max.z))
+        });
+
+        let width = ((max.x - min.x) / NAV_CELL_SIZE).ceil().max(0.0) as usize;
+        let height = ((max.y - min.y) / NAV_CELL_SIZE).ceil().max(0.0) as usize;
+        let mut grid = NavGrid {
+            origin: min,
+            width,
+            height,
+            walkable: vec![false; width * height],
+        };
+
+        for index in 0..grid.walkable.len() {
+            let center = grid.center(index);
+            grid.walkable[index] = floors
+                .iter()
+                .any(|floor| floor.contains(center.x, center.y))
+                && !walls.iter().any(|wall| {
+                    center.x <= wall.max.x + WALL_CLEARANCE
+                        && center.x >= wall.min.x - WALL_CLEARANCE
+                        && center.y <= wall.max.z + WALL_CLEARANCE
+                        && center.y >= wall.min.z - WALL_CLEARANCE
+                });
+        }
+
+        grid
+    }
+
+    fn cell(&self, position: Vec2) -> Option<(usize, usize)> {
+        let cell = ((position - self.origin) / NAV_CELL_SIZE).floor();
+        if cell.x < 0.0 || cell.y < 0.0 {
+            return None;
+        }
+
+        let (x, z) = (cell.x as usize, cell.y as usize);
+        (x < self.width && z < self.height).then_some((x, z))
+    }
+
+    fn center(&self, index: usize) -> Vec2 {
+        let x = (index % self.width) as f32 + 0.5;
+        let z = (index / self.width) as f32 + 0.5;
+
+        self.origin + Vec2::new(x, z) * NAV_CELL_SIZE
+    }
+
+    fn is_walkable(&self, x: isize, z: isize) -> bool {
+        x >= 0
+            && z >= 0
+            && (x as usize) < self.width
+            && (z as usize) < self.height
+            && self.walkable[z as usize * self.width + x as usize]
+    }
+
+    // octile distance, the cheapest a path between the cells could be
+    fn estimate(&self, from: usize, to: usize) -> u32 {
+        let dx = (from % self.width).abs_diff(to % self.width) as u32;
+        let dz = (from / self.width).abs_diff(to / self.width) as u32;
+
+        STRAIGHT_COST * dx.max(dz) + (DIAGONAL_COST - STRAIGHT_COST) * dx.min(dz)
+    }
+
+    /// A* over the cells, returns the cells from start to goal
+    fn find_path(&self, start: usize, goal: usize) -> Option<Vec<usize>> {
+        let mut costs = vec![u32::MAX; self.walkable.len()];
+        let mut came_from = vec![usize::MAX; self.walkable.len()];
+        let mut open = BinaryHeap::new();
+
+        costs[start] = 0;
+        open.push(Reverse((self.estimate(start, goal), start)));
+
+        while let Some(Reverse((_, current))) = open.pop() {
+            if current == goal {
+                let mut path = vec![goal];
+                let mut cell = goal;
+                while cell != start {
+                    cell = came_from[cell];
+                    path.push(cell);
+                }
+                path.reverse();
+
+                return Some(path);
+            }
+
+            let x = (current % self.width) as isize;
+            let z = (current / self.width) as isize;
+            for (dx, dz) in [
+                (1, 0),
+                (-1, 0),
+                (0, 1),
+                (0, -1),
+                (1, 1),
+                (1, -1),
+                (-1, 1),
+                (-1, -1),
+            ] {
+                if !self.is_walkable(x + dx, z + dz) {
+                    continue;
+                }
+
+                // don't cut corners, the burro would scrape the wall
+                let is_diagonal = dx != 0 && dz != 0;
+                if is_diagonal && (!self.is_walkable(x + dx, z) || !self.is_walkable(x, z + dz)) {
+                    continue;
+                }
+
+                let neighbor = (z + dz) as usize * self.width + (x + dx) as usize;
+                let step = if is_diagonal {
+                    DIAGONAL_COST
+                } else {
+                    STRAIGHT_COST
+                };
+                let cost = costs[current] + step;
+                if cost < costs[neighbor] {
+                    costs[neighbor] = cost;
+                    came_from[neighbor] = current;
+                    open.push(Reverse((cost + self.estimate(neighbor, goal), neighbor)));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Numbers each group of walkable cells that can be walked between
+    fn regions(&self) -> Vec<Option<usize>> {
+        let mut regions = vec![None; self.walkable.len()];
+        let mut region = 0;
+        for start in 0..self.walkable.len() {
+            if !self.walkable[start] || regions[start].is_some() {
+                continue;
+            }
+
+            regions[start] = Some(region);
+            let mut open = vec![start];
+            while let Some(current) = open.pop() {
+                let x = (current % self.width) as isize;
+                let z = (current / self.width) as isize;
+                for (dx, dz) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                    if !self.is_walkable(x + dx, z + dz) {
+                        continue;
+                    }
+
+                    let neighbor = (z + dz) as usize * self.width + (x + dx) as usize;
+                    if regions[neighbor].is_none() {
+                        regions[neighbor] = Some(region);
+                        open.push(neighbor);
+                    }
+                }
+            }
+            region += 1;
+        }
+
+        regions
+    }
+
+    /// The closest walkable cell a burro standing at `position` could step onto,
+    /// it can start up against a wall and still get away from it
+    fn nearest_walkable(&self, position: Vec2) -> Option<usize> {
+        let (x, z) = self.cell(position)?;
+        let reach = (WALL_CLEARANCE / NAV_CELL_SIZE).ceil() as isize + 1;
+
+        let mut nearest = None;
+        for dz in -reach..=reach {
+            for dx in -reach..=reach {
+                let (x, z) = (x as isize + dx, z as isize + dz);
+                if !self.is_walkable(x, z) {
+                    continue;
+                }
+
+                let cell = z as usize * self.width + x as usize;
+                let distance = self.center(cell).distance_squared(position);
+                if nearest.is_some_and(|(_, nearest)| nearest <= distance) {
+                    continue;
+                }
+                nearest = Some((cell, distance));
+            }
+        }
+
+        nearest.map(|(cell, _)| cell)
+    }
+}
+
 impl FloorManager {
     pub fn clear(&mut self) {
         self.floors.clear();
+        self.walls.clear();
+        self.nav_grid = None;
     }
 
     pub fn store_floor(&mut self, global_transform: &GlobalTransform, aabb: &Aabb) {
-        let matrix = global_transform.compute_matrix();
-        self.floors.push(Floor {
-            min: matrix.transform_point3(aabb.min().into()),
-            max: matrix.transform_point3(aabb.max().into()),
-        });
+        self.floors.push(Bounds::new(global_transform, aabb));
+        self.nav_grid = None;
     }
 
-    pub fn get_random_spot(&self) -> Option<Vec2> {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
+    /// Walls block the navigation grid around them, see [`FloorManager::build_nav_grid`]
+    pub fn store_wall(&mut self, global_transform: &GlobalTransform, aabb: &Aabb) {
+        self.walls.push(Bounds::new(global_transform, aabb));
+        self.nav_grid = None;
+    }
+
+    /// Builds the grid that [`FloorManager::find_path`] walks once the level's floors
+    /// and walls are stored, does nothing if nothing changed since the last build
+    pub fn build_nav_grid(&mut self) {
+        if self.nav_grid.is_none() && !self.floors.is_empty() {
+            self.nav_grid = Some(NavGrid::build(&self.floors, &self.walls));
+        }
+    }
 
-        if let Some(floor) = self.floors.choose(&mut rng) {
+    pub fn get_random_spot(&self, rng: &mut impl Rng) -> Option<Vec2> {
+        for _ in 0..RANDOM_SPOT_ATTEMPTS {
+            let floor = self.floors.choose(rng)?;
             let x: f32 = rng.gen_range(floor.min.x..floor.max.x);
             let z: f32 = rng.gen_range(floor.min.z..floor.max.z);
 
-            Some(Vec2::new(x, z))
-        } else {
-            None
+            let spot = Vec2::new(x, z);
+            if self.is_walkable(spot) {
+                return Some(spot);
+            }
         }
+
+        None
     }
 
-    pub fn is_walkable(&self, x: f32, z: f32) -> bool {
-        if self.floors.is_empty() {
-            return true;
+    /// Whether a burro can stand at a spot on the ground plane without running into a wall
+    pub fn is_walkable(&self, position: Vec2) -> bool {
+        match &self.nav_grid {
+            Some(nav_grid) => nav_grid
+                .cell(position)
+                .map_or(false, |(x, z)| nav_grid.is_walkable(x as isize, z as isize)),
+            None => {
+                self.floors.is_empty()
+                    || self
+                        .floors
+                        .iter()
+                        .any(|floor| floor.contains(position.x, position.y))
+            }
         }
+    }
+
+    /// How much ground the floors cover, counting any overlap twice
+    pub fn floor_area(&self) -> f32 {
+        self.floors
+            .iter()
+            .map(|floor| (floor.max.x - floor.min.x) * (floor.max.z - floor.min.z))
+            .sum()
+    }
+
+    /// How much of the floors a burro can stand on once the walls are taken out
+    pub fn walkable_area(&self) -> f32 {
+        self.nav_grid.as_ref().map_or(0.0, |nav_grid| {
+            let cells = nav_grid
+                .walkable
+                .iter()
+                .filter(|walkable| **walkable)
+                .count();
+            cells as f32 * NAV_CELL_SIZE * NAV_CELL_SIZE
+        })
+    }
+
+    /// Which group of connected floor each spot is in, spots in the same group can walk
+    /// to each other. Spots that are off the floors or boxed in by walls get none.
+    pub fn walkable_regions(&self, spots: &[Vec2]) -> Vec<Option<usize>> {
+        let Some(nav_grid) = &self.nav_grid else {
+            return vec![None; spots.len()];
+        };
+
+        let regions = nav_grid.regions();
+        spots
+            .iter()
+            .map(|spot| {
+                nav_grid
+                    .nearest_walkable(*spot)
+                    .and_then(|cell| regions[cell])
+            })
+            .collect()
+    }
+
+    /// The corners of each floor, for the bot debug overlay
+    #[cfg(feature = "bot_debug")]
+    pub fn floors(&self) -> impl Iterator<Item = (Vec3, Vec3)> + '_ {
+        self.floors.iter().map(|floor| (floor.min, floor.max))
+    }
+
+    /// The corners of each wall, for the bot debug overlay
+    #[cfg(feature = "bot_debug")]
+    pub fn walls(&self) -> impl Iterator<Item = (Vec3, Vec3)> + '_ {
+        self.walls.iter().map(|wall| (wall.min, wall.max))
+    }
+
+    /// Waypoints on the ground plane from `from` to `to` going around walls and gaps,
+    /// ending at `to`. Straight runs are merged so there's one waypoint per turn.
+    /// Without a navigation grid it's just a straight line.
+    pub fn find_path(&self, from: Vec2, to: Vec2) -> Option<Vec<Vec2>> {
+        let Some(nav_grid) = &self.nav_grid else {
+            return Some(vec![to]);
+        };
 
-        for floor in self.floors.iter() {
-            if x <= floor.max.x && x >= floor.min.x && z <= floor.max.z && z >= floor.min.z {
-                return true;
+        let (start_x, start_z) = nav_grid.cell(from)?;
+        let (goal_x, goal_z) = nav_grid.cell(to)?;
+        if !nav_grid.is_walkable(goal_x as isize, goal_z as isize) {
+            return None;
+        }
+
+        // the start can be blocked when a burro is pushed against a wall, it still has
+        // to get out of there so only the cells it moves into are checked
+        let start = start_z * nav_grid.width + start_x;
+        let goal = goal_z * nav_grid.width + goal_x;
+        let cells = nav_grid.find_path(start, goal)?;
+
+        let mut waypoints = vec![];
+        for window in cells.windows(3) {
+            let [previous, cell, next] = [window[0], window[1], window[2]].map(|cell| {
+                IVec2::new(
+                    (cell % nav_grid.width) as i32,
+                    (cell / nav_grid.width) as i32,
+                )
+            });
+            if cell - previous != next - cell {
+                waypoints.push(nav_grid.center(window[1]));
             }
         }
+        waypoints.push(to);
+
+        Some(waypoints)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a 20x20 floor with a wall across most of it at z = 10, open past x = 15
+    fn floor_manager() -> FloorManager {
+        let mut floor_manager = FloorManager::default();
+        floor_manager.store_floor(
+            &GlobalTransform::IDENTITY,
+            &Aabb::from_min_max(Vec3::ZERO, Vec3::new(20.0, 0.1, 20.0)),
+        );
+        floor_manager.store_wall(
+            &GlobalTransform::IDENTITY,
+            &Aabb::from_min_max(Vec3::new(0.0, 0.0, 9.5), Vec3::new(14.0, 2.0, 10.5)),
+        );
+        floor_manager.build_nav_grid();
+
+        floor_manager
+    }
+
+    #[test]
+    fn straight_path_is_one_waypoint() {
+        let to = Vec2::new(18.0, 2.0);
+        let path = floor_manager().find_path(Vec2::new(2.0, 2.0), to);
+
+        assert_eq!(path, Some(vec![to]));
+    }
+
+    #[test]
+    fn path_goes_around_wall() {
+        let floor_manager = floor_manager();
+        let to = Vec2::new(2.0, 18.0);
+        let path = floor_manager.find_path(Vec2::new(2.0, 2.0), to).unwrap();
+
+        assert_eq!(path.last(), Some(&to));
+        assert!(path
+            .iter()
+            .any(|waypoint| waypoint.x > 14.0 + WALL_CLEARANCE));
+        assert!(path
+            .iter()
+            .all(|waypoint| floor_manager.is_walkable(*waypoint)));
+    }
+
+    #[test]
+    fn blocked_goal_has_no_path() {
+        let path = floor_manager().find_path(Vec2::new(2.0, 2.0), Vec2::new(5.0, 10.0));
+
+        assert_eq!(path, None);
+    }
+
+    #[test]
+    fn blocked_start_still_finds_path() {
+        let floor_manager = floor_manager();
+        let from = Vec2::new(5.0, 8.8);
+        assert!(!floor_manager.is_walkable(from));
+
+        let to = Vec2::new(5.0, 2.0);
+        assert_eq!(floor_manager.find_path(from, to).unwrap().last(), Some(&to));
+    }
+
+    #[test]
+    fn walled_off_goal_has_no_path() {
+        let mut floor_manager = floor_manager();
+        floor_manager.store_wall(
+            &GlobalTransform::IDENTITY,
+            &Aabb::from_min_max(Vec3::new(13.0, 0.0, 9.5), Vec3::new(20.0, 2.0, 10.5)),
+        );
+        floor_manager.build_nav_grid();
+
+        assert_eq!(
+            floor_manager.find_path(Vec2::new(2.0, 2.0), Vec2::new(2.0, 18.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn rotated_bounds_cover_every_corner() {
+        let transform = GlobalTransform::from(Transform::from_rotation(Quat::from_rotation_y(
+            std::f32::consts::FRAC_PI_4,
+        )));
+        let bounds = Bounds::new(
+            &transform,
+            &Aabb::from_min_max(Vec3::new(-1.0, 0.0, -1.0), Vec3::new(1.0, 1.0, 1.0)),
+        );
 
-        false
+        let half_diagonal = std::f32::consts::SQRT_2;
+        assert!((bounds.min.x + half_diagonal).abs() < 1e-5);
+        assert!((bounds.max.x - half_diagonal).abs() < 1e-5);
+        assert!((bounds.min.z + half_diagonal).abs() < 1e-5);
+        assert!((bounds.max.z - half_diagonal).abs() < 1e-5);
     }
 }
@@ -1,6 +1,7 @@
-use crate::{assets, config};
+use crate::{assets, bot, config, input};
 use bevy::prelude::*;
 use rand::Rng;
+use std::collections::HashMap;
 
 pub struct GameStatePlugin;
 impl Plugin for GameStatePlugin {
@@ -14,6 +15,7 @@ pub struct GameState {
     pub burros: Vec<BurroState>,
     pub dead_burros: Vec<usize>,
     pub current_level: usize,
+    pub rounds: usize,
     pub difficulty: f32,
 }
 
@@ -29,17 +31,24 @@ impl GameState {
     pub fn initialize(
         mut burros: Vec<BurroState>,
         number_of_bots: usize,
+        bot_profiles: &[String],
         difficulty: f32,
+        rounds: usize,
         burro_assets: &Vec<assets::BurroAsset>,
+        rng: &mut impl Rng,
     ) -> Self {
         let mut available_burros: Vec<usize> = (0..burro_assets.len()).collect();
 
         let claimed_burros: Vec<usize> = burros.iter().map(|x| x.selected_burro).collect();
         available_burros.retain(|x| !claimed_burros.contains(&x));
 
-        // bots
+        // bots, going through the profiles again if there are more bots than profiles
         for i in 0..number_of_bots {
-            let index = rand::thread_rng().gen_range(0..available_burros.len());
+            let index = rng.gen_range(0..available_burros.len());
+            let bot_profile = bot_profiles
+                .get(i % bot_profiles.len().max(1))
+                .cloned()
+                .unwrap_or_else(|| bot::DEFAULT_PROFILE.to_string());
 
             burros.push(BurroState {
                 player: config::MAX_NUMBER_OF_PLAYERS as usize + i,
@@ -47,7 +56,9 @@ impl GameState {
                 outline_color: Color::BLACK,
                 score: 0,
                 is_bot: true,
+                bot_profile: Some(bot_profile),
                 hearts: vec![],
+                input_device: input::InputDevice::default(),
             });
         }
 
@@ -55,6 +66,7 @@ impl GameState {
             burros,
             dead_burros: vec![],
             current_level: 0,
+            rounds,
             difficulty,
         }
     }
@@ -63,8 +75,28 @@ impl GameState {
         self.dead_burros = vec![];
     }
 
+    /// Adds a point based on the order the burros died, the first burro to die gets
+    /// 1 point, the last burro to die gets the most points
+    pub fn add_round_scores(&mut self) {
+        let burro_points: HashMap<usize, usize> = self
+            .dead_burros
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, b)| (*b, i + 1))
+            .collect();
+        let max_score = self.dead_burros.len() + 1;
+
+        for burro in self.burros.iter_mut() {
+            let new_score = burro_points
+                .get(&burro.selected_burro)
+                .unwrap_or(&max_score);
+            burro.score += new_score;
+        }
+    }
+
     pub fn is_game_over(&self) -> bool {
-        self.current_level >= config::NUMBER_OF_LEVELS
+        self.current_level >= self.rounds
     }
 }
 
@@ -75,7 +107,10 @@ pub struct BurroState {
     pub outline_color: Color,
     pub score: usize,
     pub is_bot: bool,
+    /// Name of the profile a bot plays with, see [`bot::BotProfile`]
+    pub bot_profile: Option<String>,
     pub hearts: Vec<Entity>,
+    pub input_device: input::InputDevice,
 }
 
 #[derive(Component, Copy, Clone, PartialEq, Debug)]
@@ -0,0 +1,115 @@
+use bevy::{prelude::*, render::primitives::Aabb};
+use gltf::buffer::Source;
+use std::{fs, path::Path};
+
+/// What the simulation needs from a level, read straight from its glb. These are the
+/// same meshes the scene hook in ingame looks for, without loading the rest of the scene.
+#[derive(Default)]
+pub struct LevelGeometry {
+    pub floors: Vec<(GlobalTransform, Aabb)>,
+    pub spawn_points: Vec<Vec3>,
+    /// Each spawn point's mesh name, in the same order as `spawn_points`
+    pub spawn_point_names: Vec<String>,
+    pub walls: Vec<Wall>,
+    /// Names of the `Cube` meshes without vertices to build a collider from
+    pub broken_colliders: Vec<String>,
+}
+
+/// A wall's triangles, already moved into place
+pub struct Wall {
+    pub vertices: Vec<Vec3>,
+    pub indices: Vec<[u32; 3]>,
+    // the floor has a collider like the walls but doesn't block the bots' paths
+    pub is_floor: bool,
+}
+
+impl Wall {
+    pub fn aabb(&self) -> Aabb {
+        let min = self
+            .vertices
+            .iter()
+            .fold(Vec3::MAX, |min, vertex| min.min(*vertex));
+        let max = self
+            .vertices
+            .iter()
+            .fold(Vec3::MIN, |max, vertex| max.max(*vertex));
+
+        Aabb::from_min_max(min, max)
+    }
+}
+
+impl LevelGeometry {
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let bytes = fs::read(path).map_err(|error| error.to_string())?;
+        let gltf = gltf::Gltf::from_slice(&bytes).map_err(|error| error.to_string())?;
+        let scene = gltf.scenes().next().ok_or("it has no scenes")?;
+
+        let mut geometry = LevelGeometry::default();
+        for node in scene.nodes() {
+            geometry.read_node(&node, Mat4::IDENTITY, gltf.blob.as_deref());
+        }
+
+        Ok(geometry)
+    }
+
+    fn read_node(&mut self, node: &gltf::Node, parent: Mat4, blob: Option<&[u8]>) {
+        let matrix = parent * Mat4::from_cols_array_2d(&node.transform().matrix());
+
+        if let Some(mesh) = node.mesh() {
+            let primitive_count = mesh.primitives().len();
+            for primitive in mesh.primitives() {
+                // bevy names each primitive's entity after its mesh, which is what the hook sees
+                let mesh_name = mesh.name().unwrap_or("Mesh");
+                let name = if primitive_count > 1 {
+                    format!("{}.{}", mesh_name, primitive.index())
+                } else {
+                    mesh_name.to_string()
+                };
+
+                let bounds = primitive.bounding_box();
+                let aabb = Aabb::from_min_max(bounds.min.into(), bounds.max.into());
+
+                if name.contains("Cube") {
+                    let reader = primitive.reader(|buffer| match buffer.source() {
+                        Source::Bin => blob,
+                        Source::Uri(_) => None,
+                    });
+                    if let Some(positions) = reader.read_positions() {
+                        let vertices: Vec<Vec3> = positions
+                            .map(|position| matrix.transform_point3(position.into()))
+                            .collect();
+                        let indices: Vec<u32> = match reader.read_indices() {
+                            Some(indices) => indices.into_u32().collect(),
+                            None => (0..vertices.len() as u32).collect(),
+                        };
+
+                        self.walls.push(Wall {
+                            vertices,
+                            indices: indices
+                                .chunks_exact(3)
+                                .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+                                .collect(),
+                            is_floor: name.contains("floor"),
+                        });
+                    } else {
+                        self.broken_colliders.push(name.clone());
+                    }
+                }
+
+                if name.contains("floor") {
+                    self.floors.push((GlobalTransform::from(matrix), aabb));
+                }
+
+                if name.contains("spawn_point") {
+                    self.spawn_points
+                        .push(matrix.transform_point3(aabb.center.into()));
+                    self.spawn_point_names.push(name);
+                }
+            }
+        }
+
+        for child in node.children() {
+            self.read_node(&child, matrix, blob);
+        }
+    }
+}
@@ -0,0 +1,557 @@
+use crate::{
+    asset_loading, assets, audio, bot, bullet, burro, cleanup, cli, config, floor, game_state, hit,
+    ingame, is_leaving_match, levels, menu::settings::state::SettingsMenuState, persistence,
+    player, rng, simulation, weapon, AppState, IngameState,
+};
+use bevy::{
+    app::{AppExit, ScheduleRunnerPlugin},
+    ecs::system::Command,
+    log::{Level, LogPlugin},
+    prelude::*,
+    scene::ScenePlugin,
+    time::TimeUpdateStrategy,
+};
+use bevy_rapier3d::prelude::*;
+use bevy_toon_shader::ToonShaderMaterial;
+use rand::Rng;
+use std::{collections::BTreeMap, fs, path::Path, time::Duration};
+
+pub mod level;
+pub mod validate;
+
+// rounds that go on longer than this are called a draw
+const MAX_ROUND_SECONDS: f32 = 180.0;
+const MIN_BOTS: usize = 2;
+
+/// Plays bot only matches with no window, renderer or audio as fast as possible,
+/// then prints how each burro did on every level
+pub fn run(
+    launch_options: &cli::LaunchOptions,
+    level_manifest: &levels::LevelManifest,
+    matches_per_level: usize,
+) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::ZERO)),
+        LogPlugin {
+            level: Level::WARN,
+            ..default()
+        },
+        AssetPlugin::default(),
+        TransformPlugin,
+        HierarchyPlugin,
+        ScenePlugin,
+    ))
+    // every update runs exactly one tick
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+        1.0 / simulation::TICKS_PER_SECOND,
+    )))
+    .add_asset::<Mesh>()
+    .add_asset::<StandardMaterial>()
+    .add_asset::<ToonShaderMaterial>()
+    .add_state::<AppState>()
+    .add_state::<IngameState>()
+    .add_event::<hit::CreateHitEvent>()
+    .init_resource::<SettingsMenuState>()
+    .init_resource::<audio::AudioSettings>()
+    .insert_resource(config::GameConfiguration::default())
+    .insert_resource(config::MatchRules::default())
+    .insert_resource(level_manifest.clone())
+    .add_plugins(RapierPhysicsPlugin::<NoUserData>::default().with_default_system_setup(false))
+    .add_plugins((
+        assets::AssetsPlugin,
+        bot::BotPlugin,
+        bullet::BulletPlugin,
+        burro::BurroPlugin,
+        floor::FloorPlugin,
+        game_state::GameStatePlugin,
+        ingame::InGameSimulationPlugin,
+        levels::LevelsPlugin,
+        rng::RngPlugin,
+        simulation::SimulationPlugin,
+        weapon::WeaponPlugin,
+        HeadlessPlugin,
+    ));
+
+    // play with the same settings the game would
+    persistence::LoadSettings.apply(&mut app.world);
+    load_burros(&mut app.world);
+    load_weapons(&mut app.world);
+    load_bot_profiles(&mut app.world);
+
+    let simulation = match Simulation::new(
+        &app.world,
+        launch_options,
+        level_manifest,
+        matches_per_level,
+    ) {
+        Ok(simulation) => simulation,
+        Err(error) => {
+            eprintln!("{}", error);
+            return;
+        }
+    };
+    println!(
+        "Simulating {} matches of {} rounds per level with {} bots ({})",
+        matches_per_level,
+        app.world.resource::<config::MatchRules>().rounds,
+        simulation.bots,
+        simulation.bot_profiles.join(", ")
+    );
+
+    app.insert_resource(simulation);
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::LoadInGame);
+    app.run();
+}
+
+struct HeadlessPlugin;
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::LoadInGame), start_round)
+            .add_systems(
+                OnExit(AppState::InGame),
+                cleanup::<CleanupMarker>.run_if(is_leaving_match),
+            )
+            .add_systems(OnEnter(IngameState::ScoreDisplay), finish_round)
+            .add_systems(
+                FixedUpdate,
+                time_out_rounds
+                    .after(simulation::SimulationSet::Record)
+                    .run_if(in_state(AppState::InGame).and_then(in_state(IngameState::InGame))),
+            );
+    }
+}
+
+#[derive(Component)]
+struct CleanupMarker;
+
+#[derive(Resource)]
+struct Simulation {
+    level_manifest: levels::LevelManifest,
+    levels: Vec<usize>,
+    level_index: usize,
+    loaded_level: Option<usize>,
+    geometry: level::LevelGeometry,
+    matches_per_level: usize,
+    match_number: usize,
+    matches_played: usize,
+    bots: usize,
+    bot_profiles: Vec<String>,
+    difficulty: f32,
+    seed: Option<u64>,
+    round_ticks: u32,
+    mechaburro: Option<usize>,
+    results: Vec<LevelResults>,
+}
+
+#[derive(Default)]
+struct LevelResults {
+    level: usize,
+    name: String,
+    matches: usize,
+    rounds: usize,
+    draws: usize,
+    ticks: u64,
+    mechaburro_wins: usize,
+    // keyed by selected burro
+    burros: BTreeMap<usize, BurroResults>,
+}
+
+#[derive(Default)]
+struct BurroResults {
+    matches: usize,
+    match_wins: usize,
+    rounds: usize,
+    round_wins: usize,
+}
+
+impl Simulation {
+    fn new(
+        world: &World,
+        launch_options: &cli::LaunchOptions,
+        level_manifest: &levels::LevelManifest,
+        matches_per_level: usize,
+    ) -> Result<Self, String> {
+        let settings_menu = world.resource::<SettingsMenuState>();
+        let game_config = world.resource::<config::GameConfiguration>();
+        let game_assets = world.resource::<assets::GameAssets>();
+        let burro_count = game_assets.burro_assets.len();
+        let profiles = game_assets.bot_profiles.keys().cloned().collect::<Vec<_>>();
+        launch_options.check_bot_profiles(&profiles)?;
+
+        let max_bots = (config::MAX_NUMBER_OF_PLAYERS as usize).min(burro_count);
+        if max_bots < MIN_BOTS {
+            return Err(format!("Need at least {} burros to simulate", MIN_BOTS));
+        }
+
+        let bots = launch_options
+            .bots
+            .unwrap_or(settings_menu.number_of_bots.max(0) as usize)
+            .clamp(MIN_BOTS, max_bots);
+        let difficulty = launch_options
+            .difficulty
+            .unwrap_or(settings_menu.unfair_advantage as f32 + cli::MIN_DIFFICULTY)
+            .max(cli::MIN_DIFFICULTY);
+        let levels = match launch_options.level {
+            Some(level) => vec![level],
+            None => (0..level_manifest.count()).collect(),
+        };
+
+        Ok(Simulation {
+            level_manifest: level_manifest.clone(),
+            levels,
+            level_index: 0,
+            loaded_level: None,
+            geometry: level::LevelGeometry::default(),
+            matches_per_level,
+            match_number: 0,
+            matches_played: 0,
+            bots,
+            bot_profiles: launch_options.bot_profiles(bots, settings_menu),
+            difficulty,
+            seed: launch_options.seed.or(game_config.seed),
+            round_ticks: 0,
+            mechaburro: None,
+            results: vec![],
+        })
+    }
+
+    /// Makes sure the current level is loaded, skipping any that can't be played.
+    /// Returns false once every level has been played.
+    fn load_level(&mut self) -> bool {
+        while let Some(&level) = self.levels.get(self.level_index) {
+            if self.loaded_level == Some(level) {
+                return true;
+            }
+
+            let name = &self.level_manifest.level(level).name;
+            let path = Path::new("assets").join(&self.level_manifest.level(level).scene);
+            match level::LevelGeometry::read(&path) {
+                Ok(geometry) => {
+                    self.results.push(LevelResults {
+                        level,
+                        name: name.clone(),
+                        ..default()
+                    });
+                    self.geometry = geometry;
+                    self.loaded_level = Some(level);
+                    return true;
+                }
+                Err(error) => warn!(
+                    "Skipping {}, couldn't read {}: {}",
+                    name,
+                    path.display(),
+                    error
+                ),
+            }
+
+            self.level_index += 1;
+        }
+
+        false
+    }
+
+    /// Each match gets its own seed so a single match can be played again
+    fn match_seed(&self) -> Option<u64> {
+        self.seed
+            .map(|seed| seed.wrapping_add(self.matches_played as u64))
+    }
+
+    fn end_round(&mut self, game_state: &mut game_state::GameState, winner: Option<usize>) {
+        let Some(results) = self.results.last_mut() else {
+            return;
+        };
+
+        results.rounds += 1;
+        results.ticks += self.round_ticks as u64;
+        for burro in game_state.burros.iter() {
+            results
+                .burros
+                .entry(burro.selected_burro)
+                .or_default()
+                .rounds += 1;
+        }
+        match winner {
+            Some(winner) => {
+                results.burros.entry(winner).or_default().round_wins += 1;
+                if self.mechaburro == Some(winner) {
+                    results.mechaburro_wins += 1;
+                }
+            }
+            None => results.draws += 1,
+        }
+
+        game_state.add_round_scores();
+        game_state.current_level += 1;
+        if !game_state.is_game_over() {
+            return;
+        }
+
+        results.matches += 1;
+        for burro in game_state.burros.iter() {
+            results
+                .burros
+                .entry(burro.selected_burro)
+                .or_default()
+                .matches += 1;
+        }
+        // ties go to whoever comes first
+        if let Some(winner) = game_state
+            .burros
+            .iter()
+            .rev()
+            .max_by_key(|burro| burro.score)
+        {
+            results
+                .burros
+                .entry(winner.selected_burro)
+                .or_default()
+                .match_wins += 1;
+        }
+
+        self.matches_played += 1;
+        self.match_number += 1;
+        if self.match_number >= self.matches_per_level {
+            self.match_number = 0;
+            self.level_index += 1;
+        }
+    }
+
+    fn print_results(&self, burro_assets: &[assets::BurroAsset]) {
+        for results in self.results.iter() {
+            let average_round =
+                results.ticks as f32 / results.rounds.max(1) as f32 / simulation::TICKS_PER_SECOND;
+
+            println!();
+            println!(
+                "{} (--level {}): {} matches, {} rounds, {} draws, rounds last {:.1}s on average",
+                results.name,
+                results.level,
+                results.matches,
+                results.rounds,
+                results.draws,
+                average_round
+            );
+            println!(
+                "  Mechaburro won {:.1}% of rounds",
+                percentage(results.mechaburro_wins, results.rounds)
+            );
+
+            let mut burros: Vec<_> = results.burros.iter().collect();
+            burros.sort_by(|(_, a), (_, b)| {
+                percentage(b.match_wins, b.matches).total_cmp(&percentage(a.match_wins, a.matches))
+            });
+            for (selected_burro, burro) in burros {
+                let name = burro_assets
+                    .get(*selected_burro)
+                    .map_or("?", |burro| burro.name.as_str());
+                println!(
+                    "  {:<16} won {:>5.1}% of {} matches, {:>5.1}% of {} rounds",
+                    name,
+                    percentage(burro.match_wins, burro.matches),
+                    burro.matches,
+                    percentage(burro.round_wins, burro.rounds),
+                    burro.rounds
+                );
+            }
+        }
+    }
+}
+
+fn percentage(count: usize, total: usize) -> f32 {
+    if total == 0 {
+        return 0.0;
+    }
+
+    100.0 * count as f32 / total as f32
+}
+
+/// Only the burros' names are needed, their textures are never drawn
+fn load_burros(world: &mut World) {
+    let mut game_assets = world.resource_mut::<assets::GameAssets>();
+    let Ok(entries) = fs::read_dir("assets/textures/burros") else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let file_path = entry.path();
+        if file_path
+            .extension()
+            .map_or(true, |extension| extension != "png")
+        {
+            continue;
+        }
+        let Some(name) = file_path.file_stem().and_then(|name| name.to_str()) else {
+            continue;
+        };
+
+        game_assets.burro_assets.push(assets::BurroAsset {
+            name: name.replace('_', " ").to_uppercase(),
+            ..default()
+        });
+    }
+}
+
+fn load_weapons(world: &mut World) {
+    for (name, weapon) in read_folder("weapons", weapon::WEAPON_EXTENSION, |bytes| {
+        weapon::read_weapon(bytes).map_err(|error| error.to_string())
+    }) {
+        let handle = world.resource_mut::<Assets<weapon::Weapon>>().add(weapon);
+        world
+            .resource_mut::<assets::GameAssets>()
+            .weapons
+            .insert(name, handle);
+    }
+}
+
+fn load_bot_profiles(world: &mut World) {
+    for (name, profile) in read_folder("bots", bot::BOT_PROFILE_EXTENSION, |bytes| {
+        ron::de::from_bytes::<bot::BotProfile>(bytes).map_err(|error| error.to_string())
+    }) {
+        let handle = world.resource_mut::<Assets<bot::BotProfile>>().add(profile);
+        world
+            .resource_mut::<assets::GameAssets>()
+            .bot_profiles
+            .insert(name, handle);
+    }
+}
+
+/// Reads every `*.<extension>` file in a folder of the assets right away, named after the
+/// file. Files that can't be read are skipped.
+fn read_folder<T>(
+    folder: &str,
+    extension: &str,
+    read: impl Fn(&[u8]) -> Result<T, String>,
+) -> Vec<(String, T)> {
+    let mut assets = vec![];
+    for (name, path) in asset_loading::folder_assets(folder, extension) {
+        let file_path = Path::new("assets").join(path);
+        match fs::read(&file_path)
+            .map_err(|error| error.to_string())
+            .and_then(|bytes| read(&bytes))
+        {
+            Ok(asset) => assets.push((name, asset)),
+            Err(error) => warn!("Couldn't read {}: {}", file_path.display(), error),
+        }
+    }
+
+    assets
+}
+
+fn start_round(
+    mut commands: Commands,
+    mut simulation: ResMut<Simulation>,
+    mut game_state: ResMut<game_state::GameState>,
+    mut game_rng: ResMut<rng::GameRng>,
+    mut floor_manager: ResMut<floor::FloorManager>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut next_ingame_state: ResMut<NextState<IngameState>>,
+    mut app_exit: EventWriter<AppExit>,
+    match_rules: Res<config::MatchRules>,
+    game_config: Res<config::GameConfiguration>,
+    game_assets: Res<assets::GameAssets>,
+) {
+    if !simulation.load_level() {
+        simulation.print_results(&game_assets.burro_assets);
+        app_exit.send(AppExit);
+        return;
+    }
+
+    if game_state.burros.is_empty() || game_state.is_game_over() {
+        game_rng.start_match(simulation.match_seed());
+        *game_state = game_state::GameState::initialize(
+            vec![],
+            simulation.bots,
+            &simulation.bot_profiles,
+            simulation.difficulty,
+            match_rules.rounds,
+            &game_assets.burro_assets,
+            &mut game_rng.gameplay,
+        );
+    }
+    game_rng.start_round(None);
+    game_state.on_new_level();
+
+    let geometry = &simulation.geometry;
+    floor_manager.clear();
+    for (global_transform, aabb) in geometry.floors.iter() {
+        floor_manager.store_floor(global_transform, aabb);
+    }
+    for wall in geometry.walls.iter() {
+        if !wall.is_floor {
+            floor_manager.store_wall(&GlobalTransform::IDENTITY, &wall.aabb());
+        }
+        commands.spawn((
+            Collider::trimesh(wall.vertices.clone(), wall.indices.clone()),
+            CollisionGroups::new(Group::GROUP_1, Group::ALL),
+            TransformBundle::default(),
+            CleanupMarker,
+        ));
+    }
+    floor_manager.build_nav_grid();
+
+    let mechaburro = game_rng.gameplay.gen_range(0..game_state.burros.len());
+    let spots = ingame::spawn_spots(
+        &geometry.spawn_points,
+        game_state.burros.len(),
+        game_config.spawn_spacing,
+        game_config
+            .rotate_spawn_points
+            .then_some(game_state.current_level),
+        &floor_manager,
+        &mut game_rng.gameplay,
+    );
+    for (i, (burro_state, point)) in game_state.burros.iter().zip(spots).enumerate() {
+        let mut burro = burro::Burro::new(
+            burro_state.selected_burro,
+            match_rules.starting_hearts,
+            &mut game_rng.cosmetic,
+        );
+        burro.is_mechaburro = i == mechaburro;
+
+        commands.spawn((
+            ingame::burro_body(Vec3::new(point.x, 0.5, point.z)),
+            burro,
+            game_state::PlayerMarker(burro_state.player),
+            player::BurroMovement::default(),
+            bot::BotBundle::new(&game_assets, burro_state.bot_profile.as_deref()),
+            CleanupMarker,
+        ));
+    }
+
+    simulation.mechaburro = Some(game_state.burros[mechaburro].selected_burro);
+    simulation.round_ticks = 0;
+    next_state.set(AppState::InGame);
+    next_ingame_state.set(IngameState::InGame);
+}
+
+fn finish_round(
+    mut simulation: ResMut<Simulation>,
+    mut game_state: ResMut<game_state::GameState>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let winner = game_state
+        .burros
+        .iter()
+        .map(|burro| burro.selected_burro)
+        .find(|burro| !game_state.dead_burros.contains(burro));
+
+    simulation.end_round(&mut game_state, winner);
+    next_state.set(AppState::LoadInGame);
+}
+
+fn time_out_rounds(
+    mut simulation: ResMut<Simulation>,
+    mut game_state: ResMut<game_state::GameState>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    simulation.round_ticks += 1;
+    if (simulation.round_ticks as f32) < MAX_ROUND_SECONDS * simulation::TICKS_PER_SECOND {
+        return;
+    }
+
+    simulation.end_round(&mut game_state, None);
+    next_state.set(AppState::LoadInGame);
+}
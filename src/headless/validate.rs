@@ -0,0 +1,314 @@
+use super::level::LevelGeometry;
+use crate::{config, floor, ingame};
+use bevy::prelude::*;
+use std::{collections::BTreeMap, path::Path};
+
+// a full match has one burro per player, any without a spawn point starts on a random spot
+const MIN_SPAWN_POINTS: usize = config::MAX_NUMBER_OF_PLAYERS as usize;
+// the winner's circle puts the top three on the spawn points ending in 0, 1 and 2
+const PODIUM_PLACES: usize = 3;
+
+/// What a glb is checked as
+#[derive(Clone, Copy)]
+pub enum LevelKind {
+    Level,
+    /// The winner's circle
+    Stage,
+}
+
+/// What was found in a level, errors are what would break a match on it
+#[derive(Default)]
+pub struct LevelReport {
+    pub notes: Vec<String>,
+    pub warnings: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// Reads the level the way the scene hook would see it
+pub fn check(path: &Path, kind: LevelKind) -> LevelReport {
+    match LevelGeometry::read(path) {
+        Ok(geometry) => check_geometry(&geometry, kind),
+        Err(error) => LevelReport {
+            errors: vec![format!("couldn't read it: {}", error)],
+            ..default()
+        },
+    }
+}
+
+fn check_geometry(geometry: &LevelGeometry, kind: LevelKind) -> LevelReport {
+    let mut report = LevelReport::default();
+    report.notes.push(format!(
+        "{} spawn points: {}",
+        geometry.spawn_points.len(),
+        geometry.spawn_point_names.join(", ")
+    ));
+    match kind {
+        LevelKind::Level if geometry.spawn_points.len() < MIN_SPAWN_POINTS => {
+            report.warnings.push(format!(
+                "fewer than {} spawn_point meshes, the rest of the burros start on random spots",
+                MIN_SPAWN_POINTS
+            ));
+        }
+        LevelKind::Level => {}
+        LevelKind::Stage => {
+            for place in 0..PODIUM_PLACES {
+                let has_place = geometry
+                    .spawn_point_names
+                    .iter()
+                    .any(|name| ingame::spawn_point_place(name) == Some(place));
+                if !has_place {
+                    report
+                        .errors
+                        .push(format!("no spawn_point ending in {}", place));
+                }
+            }
+        }
+    }
+
+    let floor_colliders = geometry.walls.iter().filter(|wall| wall.is_floor).count();
+    report.notes.push(format!(
+        "{} collider meshes, {} of them floors",
+        geometry.walls.len(),
+        floor_colliders
+    ));
+    for name in geometry.broken_colliders.iter() {
+        report
+            .errors
+            .push(format!("{} has no vertices to make a collider from", name));
+    }
+    if geometry.walls.len() == floor_colliders {
+        report
+            .warnings
+            .push("no Cube meshes to use as walls".to_string());
+    }
+
+    if geometry.floors.is_empty() {
+        report.errors.push("no floor mesh to walk on".to_string());
+        return report;
+    }
+    if floor_colliders == 0 {
+        report
+            .warnings
+            .push("none of the floors are Cube meshes so they have no collider".to_string());
+    }
+
+    // the same floors and walls the bots find their way around
+    let mut floor_manager = floor::FloorManager::default();
+    for (global_transform, aabb) in geometry.floors.iter() {
+        floor_manager.store_floor(global_transform, aabb);
+    }
+    for wall in geometry.walls.iter().filter(|wall| !wall.is_floor) {
+        floor_manager.store_wall(&GlobalTransform::IDENTITY, &wall.aabb());
+    }
+
+    let spots = geometry
+        .spawn_points
+        .iter()
+        .map(|point| Vec2::new(point.x, point.z))
+        .collect::<Vec<_>>();
+    // without a grid this only checks the floors
+    for (spot, name) in spots.iter().zip(geometry.spawn_point_names.iter()) {
+        if !floor_manager.is_walkable(*spot) {
+            report.errors.push(format!("{} isn't over a floor", name));
+        }
+    }
+
+    floor_manager.build_nav_grid();
+    let floor_area = floor_manager.floor_area();
+    let walkable_area = floor_manager.walkable_area();
+    report.notes.push(format!(
+        "{} floors covering {:.1} square units, {:.0}% of it walkable around the walls",
+        geometry.floors.len(),
+        floor_area,
+        walkable_area / floor_area.max(f32::EPSILON) * 100.0
+    ));
+
+    // the winner's circle is stood on, not walked around
+    if matches!(kind, LevelKind::Stage) {
+        return report;
+    }
+
+    let regions = floor_manager.walkable_regions(&spots);
+    let mut region_sizes = BTreeMap::new();
+    for region in regions.iter().flatten() {
+        *region_sizes.entry(*region).or_insert(0) += 1;
+    }
+    let main_region = region_sizes
+        .into_iter()
+        .max_by_key(|(_, size)| *size)
+        .map(|(region, _)| region);
+    for (region, name) in regions.iter().zip(geometry.spawn_point_names.iter()) {
+        match region {
+            None => report.errors.push(format!("{} is boxed in by walls", name)),
+            Some(region) if Some(*region) != main_region => report.errors.push(format!(
+                "{} can't be walked to from most of the other spawn points",
+                name
+            )),
+            Some(_) => {}
+        }
+    }
+
+    report
+}
+
+/// Checks a level from the command line and prints what was found,
+/// returns false if it has errors
+pub fn run(path: &Path, kind: LevelKind) -> bool {
+    let report = check(path, kind);
+
+    println!("{}", path.display());
+    for note in report.notes.iter() {
+        println!("  {}", note);
+    }
+    for warning in report.warnings.iter() {
+        println!("  warning: {}", warning);
+    }
+    for error in report.errors.iter() {
+        println!("  error: {}", error);
+    }
+
+    if report.errors.is_empty() {
+        println!("No errors");
+    } else {
+        println!("{} errors", report.errors.len());
+    }
+
+    report.errors.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::level::Wall;
+    use super::*;
+    use bevy::render::primitives::Aabb;
+
+    // a box the scene would have as a Cube mesh, only its corners matter to the checks
+    fn wall(min: Vec3, max: Vec3, is_floor: bool) -> Wall {
+        Wall {
+            vertices: (0..8)
+                .map(|corner| {
+                    let mask = BVec3::new(corner & 1 != 0, corner & 2 != 0, corner & 4 != 0);
+                    Vec3::select(mask, max, min)
+                })
+                .collect(),
+            indices: vec![],
+            is_floor,
+        }
+    }
+
+    // a 20x20 floor with a spawn point for each name along z = 5
+    fn level(names: &[&str]) -> LevelGeometry {
+        let floor_min = Vec3::ZERO;
+        let floor_max = Vec3::new(20.0, 0.1, 20.0);
+        LevelGeometry {
+            floors: vec![(
+                GlobalTransform::IDENTITY,
+                Aabb::from_min_max(floor_min, floor_max),
+            )],
+            spawn_points: (0..names.len())
+                .map(|i| Vec3::new(2.0 + 2.0 * i as f32, 0.0, 5.0))
+                .collect(),
+            spawn_point_names: names.iter().map(|name| name.to_string()).collect(),
+            walls: vec![wall(floor_min, floor_max, true)],
+            broken_colliders: vec![],
+        }
+    }
+
+    fn full_level() -> LevelGeometry {
+        let names = (0..MIN_SPAWN_POINTS)
+            .map(|i| format!("spawn_point{}", i))
+            .collect::<Vec<_>>();
+        level(&names.iter().map(String::as_str).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn full_level_has_no_errors() {
+        let report = check_geometry(&full_level(), LevelKind::Level);
+
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+    }
+
+    #[test]
+    fn too_few_spawn_points_is_a_warning_on_a_level() {
+        let report = check_geometry(&level(&["spawn_point0", "spawn_point1"]), LevelKind::Level);
+
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        assert!(report
+            .warnings
+            .iter()
+            .any(|warning| warning.starts_with("fewer than")));
+    }
+
+    #[test]
+    fn missing_podium_places_are_errors_on_a_stage() {
+        let report = check_geometry(&level(&["spawn_point0", "spawn_point1"]), LevelKind::Stage);
+
+        assert_eq!(report.errors, vec!["no spawn_point ending in 2"]);
+    }
+
+    #[test]
+    fn podium_places_are_the_whole_number() {
+        let report = check_geometry(
+            &level(&["spawn_point10", "spawn_point1", "spawn_point12"]),
+            LevelKind::Stage,
+        );
+
+        assert_eq!(
+            report.errors,
+            vec!["no spawn_point ending in 0", "no spawn_point ending in 2"]
+        );
+    }
+
+    #[test]
+    fn spawn_point_off_the_floor_is_an_error() {
+        let mut level = full_level();
+        level.spawn_points[3] = Vec3::new(30.0, 0.0, 5.0);
+        let report = check_geometry(&level, LevelKind::Level);
+
+        assert!(report
+            .errors
+            .contains(&"spawn_point3 isn't over a floor".to_string()));
+    }
+
+    #[test]
+    fn spawn_point_inside_walls_is_an_error() {
+        let mut level = full_level();
+        level.walls.push(wall(
+            Vec3::new(12.0, 0.0, 12.0),
+            Vec3::new(18.0, 2.0, 18.0),
+            false,
+        ));
+        level.spawn_points[3] = Vec3::new(15.0, 0.0, 15.0);
+        let report = check_geometry(&level, LevelKind::Level);
+
+        assert_eq!(report.errors, vec!["spawn_point3 is boxed in by walls"]);
+    }
+
+    #[test]
+    fn walled_off_spawn_point_is_an_error() {
+        let mut level = full_level();
+        level.walls.push(wall(
+            Vec3::new(0.0, 0.0, 11.0),
+            Vec3::new(20.0, 2.0, 12.0),
+            false,
+        ));
+        level.spawn_points[3] = Vec3::new(10.0, 0.0, 16.0);
+        let report = check_geometry(&level, LevelKind::Level);
+
+        assert_eq!(
+            report.errors,
+            vec!["spawn_point3 can't be walked to from most of the other spawn points"]
+        );
+    }
+
+    #[test]
+    fn level_without_a_floor_is_an_error() {
+        let mut level = full_level();
+        level.floors.clear();
+        let report = check_geometry(&level, LevelKind::Level);
+
+        assert!(report
+            .errors
+            .contains(&"no floor mesh to walk on".to_string()));
+    }
+}
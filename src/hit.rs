@@ -1,12 +1,15 @@
-use crate::config;
+use crate::{config, rng, AppState};
 use bevy::prelude::*;
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
 pub struct HitPlugin;
 impl Plugin for HitPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (handle_create_hit_event, animate_hit))
-            .add_event::<CreateHitEvent>();
+        app.add_systems(
+            Update,
+            (handle_create_hit_event, animate_hit).run_if(not(in_state(AppState::Pause))),
+        )
+        .add_event::<CreateHitEvent>();
     }
 }
 
@@ -18,7 +21,7 @@ pub struct Hit {
 #[derive(Event)]
 pub struct CreateHitEvent {
     pub position: Vec3,
-    pub is_candy: bool,
+    pub color: Color,
 }
 
 pub fn animate_hit(
@@ -63,7 +66,9 @@ pub fn handle_create_hit_event(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     config: Res<config::GameConfiguration>,
+    mut game_rng: ResMut<rng::GameRng>,
 ) {
+    let rng = &mut game_rng.cosmetic;
     for event in create_hit_event_reader.iter() {
         let position = event.position;
 
@@ -71,21 +76,17 @@ pub fn handle_create_hit_event(
             Transform::from_xyz(position.x as f32, position.y as f32, position.z as f32);
 
         for _ in 0..6 {
-            let inner_mesh_x = thread_rng().gen_range(-25..25) as f32 / 100.0;
-            let inner_mesh_z = thread_rng().gen_range(-25..25) as f32 / 100.0;
+            let inner_mesh_x = rng.gen_range(-25..25) as f32 / 100.0;
+            let inner_mesh_z = rng.gen_range(-25..25) as f32 / 100.0;
 
-            let color = if event.is_candy {
-                Color::rgba(0.3, 0.5, 0.3, 0.7 + inner_mesh_x.abs())
-            } else {
-                Color::rgba(0.6, 0.0, 0.0, 0.7 + inner_mesh_x.abs())
-            };
+            let color = event.color.with_a(0.7 + inner_mesh_x.abs());
 
             let move_toward_x =
-                thread_rng().gen_range(config.hit_min_spread_x..config.hit_max_spread_x) as f32;
+                rng.gen_range(config.hit_min_spread_x..config.hit_max_spread_x) as f32;
             let move_toward_y =
-                thread_rng().gen_range(config.hit_min_spread_y..config.hit_max_spread_y) as f32;
+                rng.gen_range(config.hit_min_spread_y..config.hit_max_spread_y) as f32;
             let move_toward_z =
-                thread_rng().gen_range(config.hit_min_spread_z..config.hit_max_spread_z) as f32;
+                rng.gen_range(config.hit_min_spread_z..config.hit_max_spread_z) as f32;
             let move_toward = Vec3::new(move_toward_x, move_toward_y, move_toward_z);
 
             commands
@@ -1,7 +1,8 @@
 use crate::ui::follow_text::FollowTextCommandsExt;
 use crate::{
-    asset_loading, assets, bot, burro, cleanup, game_camera, game_state, player, scene_hook,
-    AppState, IngameState, shaders,
+    asset_loading, assets, audio, bot, burro, cleanup, config, floor, game_camera, game_state,
+    input, is_leaving_match, levels, player, replay, rng, scene_hook, shaders, simulation, weapon,
+    AppState, IngameState,
 };
 use bevy::ecs::system::{Command, SystemState};
 use bevy::gltf::Gltf;
@@ -9,6 +10,7 @@ use bevy::prelude::*;
 use bevy_mod_outline::{OutlineBundle, OutlineVolume, SetOutlineDepth};
 use bevy_rapier3d::prelude::*;
 use bevy_toon_shader::{ToonShaderMaterial, ToonShaderSun};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
 use std::collections::HashMap;
 use std::f32::consts::TAU;
 use std::sync::{Arc, Mutex};
@@ -18,22 +20,40 @@ mod ui;
 pub struct InGamePlugin;
 impl Plugin for InGamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::LoadInGame), setup)
-            .add_systems(OnExit(AppState::InGame), cleanup::<CleanupMarker>)
-            .add_plugins(ui::InGameUIPlugin)
+        app.add_systems(OnEnter(AppState::LoadInGame), (setup, play_level_music))
+            .add_systems(OnEnter(IngameState::ScoreDisplay), preload_next_level)
             .add_systems(
-                Update,
-                (
-                    (bot::update_bot_ai, bot::update_virtual_controllers)
-                        .chain()
-                        .run_if(|g: Res<game_state::GameState>| !g.is_game_over()),
-                    player::handle_input,
-                    player::move_player,
-                    apply_deferred,
-                )
+                OnExit(AppState::InGame),
+                cleanup::<CleanupMarker>.run_if(is_leaving_match),
+            )
+            .add_systems(
+                OnExit(AppState::Pause),
+                cleanup::<CleanupMarker>.run_if(is_leaving_match),
+            )
+            .add_plugins((ui::InGameUIPlugin, InGameSimulationPlugin));
+    }
+}
+
+/// The bots and players' part of each tick, also used by the headless simulation
+pub struct InGameSimulationPlugin;
+impl Plugin for InGameSimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                (bot::update_bot_ai, bot::update_virtual_controllers)
                     .chain()
-                    .run_if(in_state(AppState::InGame)),
-            );
+                    .run_if(|g: Res<game_state::GameState>| !g.is_game_over())
+                    .run_if(not(replay::is_replaying)),
+                player::aim_with_cursor,
+                player::handle_input,
+                player::move_player,
+                apply_deferred,
+            )
+                .chain()
+                .in_set(simulation::SimulationSet::Players)
+                .run_if(in_state(AppState::InGame)),
+        );
     }
 }
 
@@ -45,17 +65,14 @@ impl Command for IngameLoader {
             ResMut<assets::GameAssets>,
             ResMut<game_state::GameState>,
             ResMut<Assets<ToonShaderMaterial>>,
+            Res<levels::LevelManifest>,
         )> = SystemState::new(world);
-        let (mut assets_handler, mut game_assets, game_state, mut toon_materials) =
+        let (mut assets_handler, mut game_assets, game_state, mut toon_materials, level_manifest) =
             system_state.get_mut(world);
 
         assets_handler.add_font(&mut game_assets.font, "fonts/MexicanTequila.ttf");
         assets_handler.add_font(&mut game_assets.score_font, "fonts/monogram.ttf");
-        assets_handler.add_audio(&mut game_assets.bloop_sfx, "audio/bloop.wav");
-        assets_handler.add_audio(&mut game_assets.laser_sfx, "audio/laser.wav");
         //    assets_handler.add_audio(&mut game_assets.smoke_sfx, "audio/smoke.wav");
-        assets_handler.add_audio(&mut game_assets.candy_hit_sfx, "audio/candy_hit.wav");
-        assets_handler.add_audio(&mut game_assets.laser_hit_sfx, "audio/laser_hit.wav");
         assets_handler.add_glb(&mut game_assets.burro, "models/burro_new.glb");
         assets_handler.add_material(
             &mut game_assets.avatar_bottom,
@@ -74,44 +91,217 @@ impl Command for IngameLoader {
 
         assets_handler.add_material(&mut game_assets.heart_texture, "textures/heart.png", true);
 
-        let mut mechaburro_texture = asset_loading::GameTexture::default();
-        assets_handler.add_material(&mut mechaburro_texture, &"textures/mechaburro.png", false);
-        let toon_material_textured = toon_materials.add(ToonShaderMaterial {
-            base_color_texture: Some(mechaburro_texture.image.clone()),
-            color: Color::default(),
-            sun_dir: Vec3::new(0.0, 0.0, 0.0),
-            sun_color: Color::default(),
-            camera_pos: Vec3::new(0.0, 1.0, -1.0),
-            ambient_color: Color::default(),
-        });
-        game_assets.mechaburro_texture = assets::BurroAsset {
-            name: "Mechaburro".into(),
-            texture: mechaburro_texture,
-            toon_texture: toon_material_textured,
-        };
-
-        assets_handler.add_mesh(
-            &mut game_assets.candy.mesh,
-            "models/candy.gltf#Mesh0/Primitive0",
-        );
-        assets_handler.add_mesh(
-            &mut game_assets.laser.mesh,
-            "models/laser.gltf#Mesh0/Primitive0",
-        );
+        // kept between rounds once it's made
+        if toon_materials
+            .get(&game_assets.mechaburro_texture.toon_texture)
+            .is_none()
+        {
+            let mut mechaburro_texture = asset_loading::GameTexture::default();
+            assets_handler.add_material(&mut mechaburro_texture, &"textures/mechaburro.png", false);
+            let toon_material_textured = toon_materials.add(ToonShaderMaterial {
+                base_color_texture: Some(mechaburro_texture.image.clone()),
+                color: Color::default(),
+                sun_dir: Vec3::new(0.0, 0.0, 0.0),
+                sun_color: Color::default(),
+                camera_pos: Vec3::new(0.0, 1.0, -1.0),
+                ambient_color: Color::default(),
+            });
+            game_assets.mechaburro_texture = assets::BurroAsset {
+                name: "Mechaburro".into(),
+                texture: mechaburro_texture,
+                toon_texture: toon_material_textured,
+            };
+        }
+
+        for (name, path) in asset_loading::folder_assets("weapons", weapon::WEAPON_EXTENSION) {
+            let mut handle = game_assets.weapons.get(&name).cloned().unwrap_or_default();
+            assets_handler.add_weapon(&mut handle, &path);
+            game_assets.weapons.insert(name, handle);
+        }
+
+        for (name, path) in asset_loading::folder_assets("bots", bot::BOT_PROFILE_EXTENSION) {
+            let mut handle = game_assets
+                .bot_profiles
+                .get(&name)
+                .cloned()
+                .unwrap_or_default();
+            assets_handler.add_bot_profile(&mut handle, &path);
+            game_assets.bot_profiles.insert(name, handle);
+        }
 
         assets_handler.add_glb(&mut game_assets.skybox, "models/skybox.glb");
 
-        if game_state.is_game_over() {
-            assets_handler.add_material(&mut game_assets.background_image, "textures/backgrounds/trophy.png", false);
-            assets_handler.add_glb(&mut game_assets.stage, "models/stage.glb");
+        // usually preloaded while the scores were up, see preload_next_level
+        match round_level(&game_state, &level_manifest, game_state.current_level) {
+            Some(level) => {
+                assets_handler.add_material(
+                    &mut game_assets.background_image,
+                    &level.background,
+                    false,
+                );
+                assets_handler.add_glb(&mut game_assets.level, &level.scene);
+                assets_handler.add_audio(&mut game_assets.level_music, &level.music);
+            }
+            None => {
+                assets_handler.add_material(
+                    &mut game_assets.background_image,
+                    WINNER_BACKGROUND,
+                    false,
+                );
+                assets_handler.add_glb(&mut game_assets.stage, WINNER_SCENE);
+            }
+        }
+        game_assets.next_background = Handle::default();
+        game_assets.next_scene = Handle::default();
+        game_assets.next_music = Handle::default();
+    }
+}
+
+const WINNER_BACKGROUND: &str = "textures/backgrounds/trophy.png";
+const WINNER_SCENE: &str = "models/stage.glb";
+// tries at finding a random spot clear of the other burros before settling for a crowded one
+const SPAWN_SPOT_ATTEMPTS: usize = 10;
+
+/// The level a round plays in, none once the rounds are over and it's the winner's stage
+fn round_level<'a>(
+    game_state: &game_state::GameState,
+    level_manifest: &'a levels::LevelManifest,
+    round: usize,
+) -> Option<&'a levels::Level> {
+    (round < game_state.rounds).then(|| level_manifest.level(round))
+}
+
+/// Starts loading the next round while the scores are up so it's ready when the players continue
+fn preload_next_level(
+    asset_server: Res<AssetServer>,
+    game_state: Res<game_state::GameState>,
+    level_manifest: Res<levels::LevelManifest>,
+    mut game_assets: ResMut<assets::GameAssets>,
+) {
+    match round_level(&game_state, &level_manifest, game_state.current_level + 1) {
+        Some(level) => {
+            game_assets.next_background = asset_server.load(level.background.as_str());
+            game_assets.next_scene = asset_server.load(level.scene.as_str());
+            game_assets.next_music = asset_server.load(level.music.as_str());
+        }
+        None => {
+            game_assets.next_background = asset_server.load(WINNER_BACKGROUND);
+            game_assets.next_scene = asset_server.load(WINNER_SCENE);
+        }
+    }
+}
+
+/// Keeps the music going between rounds on levels that share a track
+fn play_level_music(
+    mut audio: audio::GameAudio,
+    game_state: Res<game_state::GameState>,
+    game_assets: Res<assets::GameAssets>,
+) {
+    // the last level's music carries on through the winner's circle
+    if !game_state.is_game_over() {
+        audio.continue_bgm(&game_assets.level_music);
+    }
+}
+
+/// The physics body of a burro standing at `translation`
+pub fn burro_body(translation: Vec3) -> impl Bundle {
+    (
+        RigidBody::KinematicPositionBased,
+        Collider::ball(1.0),
+        ColliderMassProperties::Density(2.0),
+        KinematicCharacterController {
+            offset: CharacterLength::Relative(0.1),
+            max_slope_climb_angle: std::f32::consts::PI / 2.0,
+            min_slope_slide_angle: 0.0,
+            slide: true,
+            translation: Some(Vec3::new(0.0, translation.y, 0.0)),
+            filter_groups: Some(CollisionGroups::new(Group::GROUP_2, Group::GROUP_1)),
+            ..default()
+        },
+        Velocity::default(),
+        CollisionGroups::new(Group::GROUP_2, Group::GROUP_1),
+        simulation::Interpolated::new(translation),
+        TransformBundle {
+            local: Transform::from_translation(translation)
+                .with_rotation(Quat::from_axis_angle(Vec3::Y, TAU * 0.5)),
+            ..default()
+        },
+    )
+}
+
+/// The place a spawn point on the winner's circle is for, the whole number its mesh name
+/// ends in so `spawn_point12` is for place 12 and not place 2
+pub fn spawn_point_place(name: &str) -> Option<usize> {
+    let number = name.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    name[number..].parse().ok()
+}
+
+/// Where each of `burros` starts the round. The level's spawn points are shuffled, or with
+/// `rotation` moved along by that many so everyone takes a turn at each of them. Points closer
+/// than `spacing` to one already handed out are passed over and any burros left without one
+/// start on a random spot of the floor.
+pub fn spawn_spots(
+    spawn_points: &[Vec3],
+    burros: usize,
+    spacing: f32,
+    rotation: Option<usize>,
+    floor_manager: &floor::FloorManager,
+    rng: &mut impl Rng,
+) -> Vec<Vec3> {
+    // the scene hook finds the points in whatever order the scene is walked, sorting them
+    // keeps a seed handing out the same points
+    let mut points = spawn_points.to_vec();
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.z.total_cmp(&b.z)));
+    match rotation {
+        Some(rotation) if !points.is_empty() => {
+            let len = points.len();
+            points.rotate_left(rotation % len);
+        }
+        Some(_) => {}
+        None => points.shuffle(rng),
+    }
+
+    let is_spaced = |spots: &[Vec3], point: Vec3| {
+        spots
+            .iter()
+            .all(|spot| Vec2::new(spot.x, spot.z).distance(Vec2::new(point.x, point.z)) >= spacing)
+    };
+
+    let mut spots = Vec::with_capacity(burros);
+    let mut passed_over = vec![];
+    for point in points {
+        if spots.len() == burros {
+            break;
+        }
+
+        if is_spaced(&spots, point) {
+            spots.push(point);
         } else {
-            assets_handler.add_material(&mut game_assets.background_image, &format!("textures/backgrounds/{:02}.png", game_state.current_level), false);
-            assets_handler.add_glb(
-                &mut game_assets.level,
-                &format!("models/level_{:02}.glb", game_state.current_level),
-            );
+            passed_over.push(point);
         }
     }
+
+    while spots.len() < burros {
+        let random_spot = (0..SPAWN_SPOT_ATTEMPTS)
+            .filter_map(|_| floor_manager.get_random_spot(rng))
+            .map(|spot| Vec3::new(spot.x, 0.0, spot.y))
+            .find(|spot| is_spaced(&spots, *spot));
+        // crowded is better than not spawning at all
+        let spot = random_spot
+            .or_else(|| passed_over.pop())
+            .or_else(|| {
+                floor_manager
+                    .get_random_spot(rng)
+                    .map(|spot| Vec3::new(spot.x, 0.0, spot.y))
+            })
+            .unwrap_or_else(|| {
+                warn!("No floor to start a burro on, it starts at the origin");
+                Vec3::ZERO
+            });
+        spots.push(spot);
+    }
+
+    spots
 }
 
 fn setup(
@@ -125,10 +315,17 @@ fn setup(
     mut next_ingame_state: ResMut<NextState<IngameState>>,
     mut shader_materials: shaders::ShaderMaterials,
     mut standard_materials: ResMut<Assets<StandardMaterial>>,
+    match_rules: Res<config::MatchRules>,
+    level_manifest: Res<levels::LevelManifest>,
+    game_config: Res<config::GameConfiguration>,
+    mut game_rng: ResMut<rng::GameRng>,
+    replay: Option<Res<replay::ReplayPlayback>>,
+    mut floor_manager: ResMut<floor::FloorManager>,
 ) {
     #[cfg(feature = "debug")]
     {
         if game_state.burros.is_empty() {
+            game_rng.start_match(game_config.seed);
             *game_state = game_state::GameState::initialize(
                 vec![game_state::BurroState {
                     player: 0,
@@ -136,37 +333,42 @@ fn setup(
                     outline_color: Color::WHITE,
                     score: 0,
                     is_bot: false,
+                    bot_profile: None,
                     hearts: vec![],
+                    input_device: input::InputDevice::Keyboard,
                 }, game_state::BurroState {
                     player: 1,
                     selected_burro: 1,
                     outline_color: Color::WHITE,
                     score: 0,
                     is_bot: false,
+                    bot_profile: None,
                     hearts: vec![],
+                    input_device: input::InputDevice::Gamepad(Gamepad { id: 0 }),
                 }],
                 0,
+                &[],
                 1.0,
+                match_rules.rounds,
                 &game_assets.burro_assets,
+                &mut game_rng.gameplay,
             );
         }
     };
 
+    // a replay plays its round again from the recorded seed
+    game_rng.start_round(replay.map(|replay| replay.round_seed()));
+
     let is_winner_display = game_state.is_game_over();
 
     game_state.on_new_level();
 
+    // the level's scene hook stores its floors and walls again
+    floor_manager.clear();
+
     // SETTING LEVEL BACKGROUND
-    *clear_color = match game_state.current_level {
-        0 => ClearColor(Color::rgb(0.55, 0.92, 0.96)), // light blue
-        1 => ClearColor(Color::rgb(1.0, 0.65, 0.62)),  // orange
-        2 => ClearColor(Color::rgb(0.72, 0.98, 0.75)), // green
-        3 => ClearColor(Color::rgb(0.81, 0.72, 0.94)), // purple
-        4 => ClearColor(Color::rgb(1.0, 0.65, 0.62)),  // orange
-        5 => ClearColor(Color::rgb(0.72, 0.98, 0.75)), // green
-        6 => ClearColor(Color::rgb(0.81, 0.72, 0.94)), // purple
-        _ => ClearColor(Color::rgb(1.0, 0.65, 0.62)),
-    };
+    let level = level_manifest.level(game_state.current_level);
+    *clear_color = ClearColor(level.clear_color());
 
     let hook_spawn_points = Arc::new(Mutex::new(vec![]));
     let on_complete_spawn_points = Arc::clone(&hook_spawn_points);
@@ -174,6 +376,15 @@ fn setup(
     let winner_hook_spawn_points = Arc::new(Mutex::new(winner_spawn_points));
     let on_complete_winner_spawn_points = Arc::clone(&winner_hook_spawn_points);
     let burro_mesh_handle = game_assets.burro.clone();
+    let starting_hearts = match_rules.starting_hearts;
+    // the burros are spawned from the scene hook, so they get their own stream
+    let burro_seed: u64 = game_rng.cosmetic.gen();
+    // where the burros start matters to the round, so it comes from the gameplay stream
+    let spawn_seed: u64 = game_rng.gameplay.gen();
+    let spawn_spacing = game_config.spawn_spacing;
+    let spawn_rotation = game_config
+        .rotate_spawn_points
+        .then_some(game_state.current_level);
 
     if let Some(gltf) = assets_gltf.get(&game_assets.skybox) {
         let material = shader_materials
@@ -181,8 +392,8 @@ fn setup(
                 .add(shaders::BackgroundMaterial {
                     texture: game_assets.background_image.image.clone(),
                     color: Color::rgba(1., 1., 1., 1.0),
-                    x_scroll_speed: 1.0,
-                    y_scroll_speed: 1.0,
+                    x_scroll_speed: level.x_scroll_speed,
+                    y_scroll_speed: level.y_scroll_speed,
                     scale: 1.0,
                 });
         let alpha_material = standard_materials.add(StandardMaterial {
@@ -239,6 +450,15 @@ fn setup(
                                         .unwrap(),
                                 )
                                 .insert(CollisionGroups::new(Group::GROUP_1, Group::ALL));
+
+                                // the floor is a cube too but bots need to walk on it
+                                if let (false, Some(global_transform), Some(aabb)) = (
+                                    name.contains("floor"),
+                                    hook_data.global_transform,
+                                    mesh.compute_aabb(),
+                                ) {
+                                    hook_data.floor_manager.store_wall(global_transform, &aabb);
+                                }
                             }
                         }
 
@@ -272,10 +492,8 @@ fn setup(
 
                                 if is_winner_display {
                                     if let Ok(mut spawn_points) = winner_hook_spawn_points.lock() {
-                                        if let Some(index) =
-                                            name.chars().last().and_then(|x| x.to_digit(10))
-                                        {
-                                            spawn_points.insert(index as usize, translation);
+                                        if let Some(place) = spawn_point_place(name) {
+                                            spawn_points.insert(place, translation);
                                         }
                                     }
                                 } else if let Ok(mut spawn_points) = hook_spawn_points.lock() {
@@ -291,15 +509,22 @@ fn setup(
                     }
                 }),
             },
-            scene_hook::SceneOnComplete::new(move |cmds, assets_gltf, game_assets, game_state| {
+            scene_hook::SceneOnComplete::new(move |cmds, assets_gltf, game_assets, game_state, floor_manager| {
                 // TODO: the spawning of burros should be moved into a separate function
                 // and the logic here should be using is_winner_display to determine which
                 // burros and which spawn points to use
+                let mut burro_rng = StdRng::seed_from_u64(burro_seed);
                 if !is_winner_display {
                     if let Ok(spawn_points) = on_complete_spawn_points.lock() {
-                        for (i, burro_state) in game_state.burros.iter().enumerate() {
-                            let point = spawn_points[i];
-
+                        let spots = spawn_spots(
+                            &spawn_points,
+                            game_state.burros.len(),
+                            spawn_spacing,
+                            spawn_rotation,
+                            floor_manager,
+                            &mut StdRng::seed_from_u64(spawn_seed),
+                        );
+                        for (burro_state, point) in game_state.burros.iter().zip(spots) {
                             let toon_material_textured = game_assets.burro_assets
                                 [burro_state.selected_burro]
                                 .toon_texture
@@ -307,43 +532,28 @@ fn setup(
 
                             if let Some(gltf) = assets_gltf.get(&burro_mesh_handle) {
                                 let mut entity_commands = cmds.spawn((
-                                    RigidBody::KinematicPositionBased,
-                                    Collider::ball(1.0),
-                                    ColliderMassProperties::Density(2.0),
-                                    KinematicCharacterController {
-                                        offset: CharacterLength::Relative(0.1),
-                                        max_slope_climb_angle: std::f32::consts::PI / 2.0,
-                                        min_slope_slide_angle: 0.0,
-                                        slide: true,
-                                        translation: Some(Vec3::new(0.0, 0.5, 0.0)),
-                                        filter_groups: Some(CollisionGroups::new(
-                                            Group::GROUP_2,
-                                            Group::GROUP_1,
-                                        )),
-                                        ..default()
-                                    },
-                                    Velocity::default(),
+                                    burro_body(Vec3::new(point.x, 0.5, point.z)),
                                     ComputedVisibility::default(),
                                     Visibility::Visible,
-                                    CollisionGroups::new(Group::GROUP_2, Group::GROUP_1),
-                                    burro::Burro::new(burro_state.selected_burro),
+                                    burro::Burro::new(
+                                        burro_state.selected_burro,
+                                        starting_hearts,
+                                        &mut burro_rng,
+                                    ),
                                     game_state::PlayerMarker(burro_state.player),
                                     player::BurroMovement::default(),
                                     CleanupMarker,
-                                    TransformBundle {
-                                        local: {
-                                            let mut t = Transform::from_xyz(point.x, 0.5, point.z);
-                                            t.rotation = Quat::from_axis_angle(Vec3::Y, TAU * 0.5);
-                                            t
-                                        },
-                                        ..default()
-                                    },
                                 ));
 
                                 if burro_state.is_bot {
-                                    entity_commands.insert(bot::BotBundle::new());
+                                    entity_commands.insert(bot::BotBundle::new(
+                                        game_assets,
+                                        burro_state.bot_profile.as_deref(),
+                                    ));
                                 } else {
-                                    entity_commands.insert(player::PlayerBundle::new());
+                                    entity_commands.insert(player::PlayerBundle::new(
+                                        burro_state.input_device,
+                                    ));
                                 }
 
                                 let outline_color = burro_state.outline_color.clone();
@@ -390,11 +600,34 @@ fn setup(
                         }
                     }
                 } else if let Ok(spawn_points) = on_complete_winner_spawn_points.lock() {
+                    // a stage missing some of the podium's spawn points still shows the winners
+                    let missing = (0..game_state.burros.len().min(3))
+                        .filter(|i| !spawn_points.contains_key(i))
+                        .collect::<Vec<_>>();
+                    if !missing.is_empty() {
+                        warn!(
+                            "The stage has no spawn_point for places {:?}, using random spots",
+                            missing
+                        );
+                    }
+                    let mut fallback_spots = spawn_spots(
+                        &[],
+                        missing.len(),
+                        spawn_spacing,
+                        None,
+                        floor_manager,
+                        &mut burro_rng,
+                    )
+                    .into_iter();
+
                     for (i, burro_state) in game_state.burros.iter().enumerate() {
                         if i > 2 {
                             continue;
                         }
-                        let point = spawn_points.get(&i).unwrap();
+                        let point = match spawn_points.get(&i) {
+                            Some(point) => *point,
+                            None => fallback_spots.next().unwrap_or_default(),
+                        };
 
                         let toon_material_textured = game_assets.burro_assets
                             [burro_state.selected_burro]
@@ -404,44 +637,27 @@ fn setup(
                         let height = 5.0;
                         if let Some(gltf) = assets_gltf.get(&burro_mesh_handle) {
                             let mut entity_commands = cmds.spawn((
-                                RigidBody::KinematicPositionBased,
-                                Collider::ball(1.0),
-                                ColliderMassProperties::Density(2.0),
-                                KinematicCharacterController {
-                                    offset: CharacterLength::Relative(0.1),
-                                    max_slope_climb_angle: std::f32::consts::PI / 2.0,
-                                    min_slope_slide_angle: 0.0,
-                                    slide: true,
-                                    translation: Some(Vec3::new(0.0, height, 0.0)),
-                                    filter_groups: Some(CollisionGroups::new(
-                                        Group::GROUP_2,
-                                        Group::GROUP_1,
-                                    )),
-                                    ..default()
-                                },
-                                Velocity::default(),
+                                burro_body(Vec3::new(point.x, height, point.z)),
                                 ComputedVisibility::default(),
                                 Visibility::Visible,
-                                CollisionGroups::new(Group::GROUP_2, Group::GROUP_1),
-                                burro::Burro::new(burro_state.selected_burro),
+                                burro::Burro::new(
+                                    burro_state.selected_burro,
+                                    starting_hearts,
+                                    &mut burro_rng,
+                                ),
                                 game_state::PlayerMarker(burro_state.player),
                                 player::BurroMovement::default(),
                                 CleanupMarker,
-                                TransformBundle {
-                                    local: {
-                                        let mut t =
-                                            Transform::from_xyz(point.x, height, point.z);
-                                        t.rotation = Quat::from_axis_angle(Vec3::Y, TAU * 0.5);
-                                        t
-                                    },
-                                    ..default()
-                                },
                             ));
 
                             if burro_state.is_bot {
-                                entity_commands.insert(bot::BotBundle::new());
+                                entity_commands.insert(bot::BotBundle::new(
+                                    game_assets,
+                                    burro_state.bot_profile.as_deref(),
+                                ));
                             } else {
-                                entity_commands.insert(player::PlayerBundle::new());
+                                entity_commands
+                                    .insert(player::PlayerBundle::new(burro_state.input_device));
                             }
 
                             let outline_color = burro_state.outline_color.clone();
@@ -546,3 +762,92 @@ fn setup(
 
 #[derive(Component)]
 struct CleanupMarker;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::render::primitives::Aabb;
+
+    fn points() -> Vec<Vec3> {
+        (0..4)
+            .map(|i| Vec3::new(i as f32 * 4.0, 0.0, 0.0))
+            .collect()
+    }
+
+    #[test]
+    fn spots_come_from_points_when_there_are_enough() {
+        let floor_manager = floor::FloorManager::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let spots = spawn_spots(&points(), 3, 2.0, None, &floor_manager, &mut rng);
+
+        assert_eq!(spots.len(), 3);
+        assert!(spots.iter().all(|spot| points().contains(spot)));
+    }
+
+    #[test]
+    fn rotation_picks_the_same_spots() {
+        let floor_manager = floor::FloorManager::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut shuffled = points();
+        shuffled.reverse();
+        let spots = spawn_spots(&shuffled, 2, 2.0, Some(1), &floor_manager, &mut rng);
+
+        assert_eq!(
+            spots,
+            vec![Vec3::new(4.0, 0.0, 0.0), Vec3::new(8.0, 0.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn close_points_are_passed_over() {
+        let floor_manager = floor::FloorManager::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let points = [
+            Vec3::ZERO,
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(4.0, 0.0, 0.0),
+        ];
+        let spots = spawn_spots(&points, 2, 2.0, Some(0), &floor_manager, &mut rng);
+
+        assert_eq!(spots, vec![Vec3::ZERO, Vec3::new(4.0, 0.0, 0.0)]);
+    }
+
+    #[test]
+    fn fewer_points_than_burros_uses_the_floor() {
+        let mut floor_manager = floor::FloorManager::default();
+        floor_manager.store_floor(
+            &GlobalTransform::IDENTITY,
+            &Aabb::from_min_max(Vec3::new(20.0, 0.0, 20.0), Vec3::new(40.0, 0.1, 40.0)),
+        );
+        floor_manager.build_nav_grid();
+        let mut rng = StdRng::seed_from_u64(0);
+        let spots = spawn_spots(&points(), 8, 2.0, None, &floor_manager, &mut rng);
+
+        assert_eq!(spots.len(), 8);
+        assert_eq!(
+            spots.iter().filter(|spot| points().contains(spot)).count(),
+            4
+        );
+        assert!(spots
+            .iter()
+            .filter(|spot| !points().contains(spot))
+            .all(|spot| floor_manager.is_walkable(Vec2::new(spot.x, spot.z))));
+    }
+
+    #[test]
+    fn fewer_points_than_burros_without_a_floor_crowds_the_origin() {
+        let floor_manager = floor::FloorManager::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let spots = spawn_spots(&[Vec3::ONE], 3, 2.0, None, &floor_manager, &mut rng);
+
+        assert_eq!(spots, vec![Vec3::ONE, Vec3::ZERO, Vec3::ZERO]);
+    }
+
+    #[test]
+    fn spawn_point_place_is_the_whole_number() {
+        assert_eq!(spawn_point_place("spawn_point0"), Some(0));
+        assert_eq!(spawn_point_place("spawn_point2"), Some(2));
+        assert_eq!(spawn_point_place("spawn_point12"), Some(12));
+        assert_eq!(spawn_point_place("spawn_point"), None);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{assets::GameAssets, burro, cleanup, game_state, ui, IngameState};
+use crate::{assets::GameAssets, burro, cleanup, config, game_state, ui, IngameState};
 use bevy::prelude::*;
 use std::collections::HashMap;
 
@@ -62,6 +62,7 @@ fn setup(
     mut commands: Commands,
     game_assets: Res<GameAssets>,
     game_state: Res<game_state::GameState>,
+    match_rules: Res<config::MatchRules>,
     mut images: ResMut<Assets<Image>>,
     window_size: Res<ui::text_size::WindowSize>,
 ) {
@@ -201,7 +202,7 @@ fn setup(
                                                 ..default()
                                             })
                                             .with_children(|builder| {
-                                                for i in 0..3 {
+                                                for i in 0..match_rules.starting_hearts {
                                                     builder.spawn((
                                                         ImageBundle {
                                                             style: Style {
@@ -252,23 +252,7 @@ fn handle_score_add_event(
     mut game_state: ResMut<game_state::GameState>,
 ) {
     if score_add_event_reader.iter().count() > 0 {
-        // adds a point based on the order the burro died
-        // first burro to die gets 1 point, last burro to die gets the most points
-        let burro_points: HashMap<usize, usize> = game_state
-            .dead_burros
-            .iter()
-            .rev()
-            .enumerate()
-            .map(|(i, b)| (*b, i + 1))
-            .collect();
-        let max_score = game_state.dead_burros.len() + 1;
-
-        for burro in game_state.burros.iter_mut() {
-            let new_score = burro_points
-                .get(&burro.selected_burro)
-                .unwrap_or(&max_score);
-            burro.score += new_score;
-        }
+        game_state.add_round_scores();
     }
 }
 
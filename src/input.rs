@@ -42,10 +42,41 @@ impl<'w, 's> InputCommandsExt for Commands<'w, 's> {
     }
 }
 
+/// The device a player joined the match with. Each human burro's in-game
+/// input map is built from this so players don't share a controller.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputDevice {
+    #[default]
+    Keyboard,
+    Gamepad(Gamepad),
+}
+
+impl InputDevice {
+    /// Figures out which device a player used to join based on what was
+    /// pressed this frame. Only players whose menu input includes the
+    /// keyboard can join with it.
+    pub fn detect(player: usize, keys: &Input<KeyCode>) -> Self {
+        let select_keys = KEYBOARD_INPUTS
+            .iter()
+            .filter(|(_, action)| *action == MenuAction::Select)
+            .map(|(key, _)| *key);
+
+        if has_keyboard(player) && keys.any_just_pressed(select_keys) {
+            InputDevice::Keyboard
+        } else {
+            InputDevice::Gamepad(Gamepad { id: player })
+        }
+    }
+}
+
+fn has_keyboard(player: usize) -> bool {
+    player == 0 || cfg!(feature = "debug")
+}
+
 pub fn create_menu_input_for_player(player: usize) -> impl Bundle {
     let mut input_map = InputMap::new(GAMEPAD_INPUTS);
     input_map.set_gamepad(Gamepad { id: player });
-    if player == 0 || cfg!(feature = "debug") {
+    if has_keyboard(player) {
         input_map.insert_multiple(KEYBOARD_INPUTS);
     }
     input_map.insert_multiple([(DualAxis::left_stick(), MenuAction::Move)]);
@@ -55,6 +86,27 @@ pub fn create_menu_input_for_player(player: usize) -> impl Bundle {
     }
 }
 
+/// Menu input for a player who has already joined a match, bound only to the
+/// device they joined with.
+pub fn create_menu_input_for_device(device: InputDevice) -> impl Bundle {
+    let mut input_map = InputMap::default();
+    match device {
+        InputDevice::Keyboard => {
+            input_map.insert_multiple(KEYBOARD_INPUTS);
+        }
+        InputDevice::Gamepad(gamepad) => {
+            input_map
+                .insert_multiple(GAMEPAD_INPUTS)
+                .insert_multiple([(DualAxis::left_stick(), MenuAction::Move)])
+                .set_gamepad(gamepad);
+        }
+    }
+    InputManagerBundle::<MenuAction> {
+        action_state: ActionState::default(),
+        input_map,
+    }
+}
+
 const KEYBOARD_INPUTS: [(KeyCode, MenuAction); 12] = [
     (KeyCode::Space, MenuAction::Select),
     (KeyCode::Return, MenuAction::Select),
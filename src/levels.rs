@@ -0,0 +1,118 @@
+use crate::{headless::validate, persistence};
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const MANIFEST_PATH: &str = "assets/config/levels.ron";
+const CUSTOM_LEVEL_DIR: &str = "levels";
+
+pub struct LevelsPlugin;
+impl Plugin for LevelsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, report_rejected_levels);
+    }
+}
+
+/// One entry of the level manifest, the paths are relative to the assets folder
+/// except a custom level's scene which is wherever it was found
+#[derive(Clone, Deserialize)]
+pub struct Level {
+    pub name: String,
+    pub scene: String,
+    pub background: String,
+    clear_color: (f32, f32, f32),
+    /// How fast the background image scrolls, see [`crate::shaders::BackgroundMaterial`]
+    pub x_scroll_speed: f32,
+    pub y_scroll_speed: f32,
+    pub music: String,
+}
+
+impl Level {
+    pub fn clear_color(&self) -> Color {
+        let (red, green, blue) = self.clear_color;
+        Color::rgb(red, green, blue)
+    }
+}
+
+/// Every level in the order they're played, custom levels come after the ones in the manifest
+#[derive(Resource, Clone, Deserialize)]
+pub struct LevelManifest {
+    levels: Vec<Level>,
+    /// Custom levels that were left out and why, logged once there's a logger
+    #[serde(skip)]
+    rejected: Vec<(PathBuf, String)>,
+}
+
+impl LevelManifest {
+    /// Read straight from disk at startup since the launch options are checked against it
+    /// before there's an asset server
+    pub fn read() -> Result<Self, String> {
+        let path = Path::new(MANIFEST_PATH);
+        let contents = fs::read_to_string(path)
+            .map_err(|error| format!("Couldn't read {}: {}", path.display(), error))?;
+        let manifest: LevelManifest = ron::from_str(&contents)
+            .map_err(|error| format!("Couldn't read {}: {}", path.display(), error))?;
+
+        if manifest.levels.is_empty() {
+            return Err(format!("{} doesn't list any levels", path.display()));
+        }
+
+        Ok(manifest)
+    }
+
+    /// Adds each glb in the custom levels folder the scene hook can play. They don't come
+    /// with a background, sky or music so they borrow the first level's.
+    pub fn add_custom_levels(&mut self) {
+        let Some(dir) = persistence::data_dir() else {
+            return;
+        };
+        let Ok(entries) = fs::read_dir(dir.join(CUSTOM_LEVEL_DIR)) else {
+            return;
+        };
+
+        let mut paths = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "glb"))
+            .collect::<Vec<_>>();
+        // read_dir's order can change, sorting keeps --level pointing at the same level
+        paths.sort();
+
+        for path in paths {
+            let Some(name) = path.file_stem().and_then(|name| name.to_str()) else {
+                continue;
+            };
+
+            let report = validate::check(&path, validate::LevelKind::Level);
+            match report.errors.into_iter().next() {
+                None => {
+                    let level = Level {
+                        name: name.to_string(),
+                        scene: path.to_string_lossy().into_owned(),
+                        ..self.levels[0].clone()
+                    };
+                    self.levels.push(level);
+                }
+                Some(error) => self.rejected.push((path, error)),
+            }
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// The level played on `round`, levels repeat if there are more rounds than levels
+    pub fn level(&self, round: usize) -> &Level {
+        &self.levels[round % self.levels.len()]
+    }
+}
+
+fn report_rejected_levels(level_manifest: Res<LevelManifest>) {
+    for (path, error) in level_manifest.rejected.iter() {
+        warn!("Skipping custom level {}, {}", path.display(), error);
+    }
+}
@@ -18,7 +18,7 @@ pub mod command_ext {
         }
     }
 
-    pub struct StateSetter(AppState);
+    pub struct StateSetter(pub AppState);
     impl Command for StateSetter {
         fn apply(self, world: &mut World) {
             let mut system_state: SystemState<(ResMut<QueueState>, ResMut<NextState<AppState>>)> =
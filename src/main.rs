@@ -1,7 +1,7 @@
 #![allow(clippy::type_complexity, clippy::too_many_arguments)]
 #![windows_subsystem = "windows"]
 
-use bevy::{app::AppExit, prelude::*};
+use bevy::prelude::*;
 use bevy_inspector_egui::bevy_egui;
 use bevy_rapier3d::prelude::*;
 use bevy_toon_shader::ToonShaderPlugin;
@@ -12,32 +12,71 @@ mod audio;
 mod bot;
 mod bullet;
 mod burro;
+mod cli;
 mod config;
 mod direction;
 mod floor;
 mod game_camera;
 mod game_state;
+mod headless;
 mod hit;
 mod ingame;
 mod input;
+mod levels;
 mod loading;
 mod mecha_picker;
 mod menu;
+mod persistence;
 mod player;
+mod replay;
+mod rng;
 mod scene_hook;
 mod shaders;
+mod simulation;
 mod smoke;
 mod ui;
 mod util;
+mod weapon;
 
 #[cfg(feature = "fps")]
 mod debug;
 
 fn main() {
+    let launch_options = cli::LaunchOptions::from_env();
+    // checking a level doesn't need the game's assets, so it works from anywhere
+    if let Some((path, kind)) = &launch_options.check_level {
+        let is_valid = headless::validate::run(path, *kind);
+        std::process::exit(if is_valid { 0 } else { 1 });
+    }
+
+    let mut level_manifest = match levels::LevelManifest::read() {
+        Ok(level_manifest) => level_manifest,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
+    level_manifest.add_custom_levels();
+    launch_options.exit_if_level_missing(level_manifest.count());
+    if let Some(matches_per_level) = launch_options.simulate {
+        headless::run(&launch_options, &level_manifest, matches_per_level);
+        return;
+    }
+
     let mut app = App::new();
-    app.add_plugins(DefaultPlugins)
-        .add_state::<AppState>()
-        .add_state::<IngameState>();
+
+    #[cfg(not(feature = "hot_reload"))]
+    app.add_plugins(DefaultPlugins);
+
+    #[cfg(feature = "hot_reload")]
+    app.add_plugins(DefaultPlugins.set(AssetPlugin {
+        watch_for_changes: bevy::asset::ChangeWatcher::with_delay(
+            std::time::Duration::from_millis(200),
+        ),
+        ..default()
+    }));
+
+    app.add_state::<AppState>().add_state::<IngameState>();
 
     #[cfg(feature = "inspect")]
     {
@@ -45,9 +84,15 @@ fn main() {
         app.add_plugins(WorldInspectorPlugin::new());
     }
 
+    #[cfg(feature = "debug")]
+    app.add_systems(Update, debug);
+
     #[cfg(feature = "lines")]
     app.add_plugins(RapierDebugRenderPlugin::default());
 
+    #[cfg(feature = "bot_debug")]
+    app.add_plugins(bot::debug::BotDebugPlugin);
+
     #[cfg(feature = "fps")]
     {
         use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
@@ -63,10 +108,15 @@ fn main() {
         ..default()
     })
     .insert_resource(config::GameConfiguration::default())
-    .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
+    .insert_resource(config::MatchRules::default())
+    .insert_resource(launch_options)
+    .insert_resource(level_manifest)
+    .add_plugins(RapierPhysicsPlugin::<NoUserData>::default().with_default_system_setup(false))
     .add_plugins((
         shaders::ShaderPlugin,
         menu::character_select::CharacterSelectPlugin,
+        menu::loading_screen::LoadingScreenPlugin,
+        menu::pause::PausePlugin,
         menu::settings::SettingsMenuPlugin,
         menu::splash::SplashPlugin,
         menu::title_screen::TitlePlugin,
@@ -93,8 +143,14 @@ fn main() {
         scene_hook::HookPlugin,
         ui::text_size::TextSizePlugin,
         ui::follow_text::FollowTextPlugin,
+        weapon::WeaponPlugin,
+        persistence::PersistencePlugin,
+        config::ConfigPlugin,
+        levels::LevelsPlugin,
+        rng::RngPlugin,
+        simulation::SimulationPlugin,
+        replay::ReplayPlugin,
     ))
-    .add_systems(Update, debug)
     .add_systems(Update, bootstrap.run_if(in_state(AppState::Initial)))
     .run();
 }
@@ -114,6 +170,7 @@ pub enum AppState {
     MechaPicker,
     ScoreDisplay,
     Loading,
+    LoadingError,
     Splash,
 }
 
@@ -127,8 +184,33 @@ pub enum IngameState {
 }
 
 use loading::command_ext::*;
-fn bootstrap(mut commands: Commands, mut clear_color: ResMut<ClearColor>) {
-    clear_color.0 = Color::hex("000000").unwrap();
+fn bootstrap(
+    mut commands: Commands,
+    mut clear_color: ResMut<ClearColor>,
+    launch_options: Res<cli::LaunchOptions>,
+    asset_server: Res<AssetServer>,
+    configuration_handle: Res<config::GameConfigurationHandle>,
+    mut settings_loaded: Local<bool>,
+) {
+    // the saved tuning values have to be in before game.config.ron is first read
+    if !*settings_loaded {
+        clear_color.0 = Color::hex("000000").unwrap();
+        commands.add(persistence::LoadSettings);
+        *settings_loaded = true;
+    }
+
+    if let Some(path) = &launch_options.replay {
+        commands.add(replay::LoadReplay(path.clone()));
+        return;
+    }
+
+    // the match is set up with the configuration's seed, so wait for it to be read
+    if launch_options.quick_launch() {
+        if configuration_handle.is_read(&asset_server) {
+            commands.add(cli::QuickLaunch);
+        }
+        return;
+    }
 
     #[cfg(feature = "debug")]
     {
@@ -136,19 +218,30 @@ fn bootstrap(mut commands: Commands, mut clear_color: ResMut<ClearColor>) {
     }
 
     #[cfg(not(feature = "debug"))]
-    commands.load_state(AppState::Splash);
+    if launch_options.skip_intro {
+        commands.load_state(AppState::TitleScreen);
+    } else {
+        commands.load_state(AppState::Splash);
+    }
 }
 
+#[cfg(feature = "debug")]
 fn debug(
     //    mut commands: Commands,
     keys: Res<Input<KeyCode>>,
-    mut exit: ResMut<Events<AppExit>>,
+    mut exit: ResMut<Events<bevy::app::AppExit>>,
 ) {
     if keys.just_pressed(KeyCode::Q) {
-        exit.send(AppExit);
+        exit.send(bevy::app::AppExit);
     }
 }
 
+/// Run condition for `OnExit` cleanups of match entities. Moving between
+/// `InGame` and `Pause` keeps the round alive, anything else tears it down.
+pub fn is_leaving_match(state: Res<State<AppState>>) -> bool {
+    !matches!(state.get(), AppState::InGame | AppState::Pause)
+}
+
 pub fn cleanup<T: Component>(mut commands: Commands, entities: Query<Entity, With<T>>) {
     for entity in entities.iter() {
         commands.entity(entity).despawn_recursive();
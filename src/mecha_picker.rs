@@ -1,9 +1,11 @@
-use crate::{assets, bullet, burro, cleanup, game_camera, game_state, ui, AppState, IngameState};
+use crate::{
+    assets, bullet, burro, cleanup, cli, config, game_camera, game_state, replay, rng, ui,
+    AppState, IngameState,
+};
 use bevy::prelude::*;
 use bevy_mod_outline::OutlineVolume;
 use bevy_toon_shader::ToonShaderMaterial;
-use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::seq::{IteratorRandom, SliceRandom};
 
 pub struct MechaPickerPlugin;
 impl Plugin for MechaPickerPlugin {
@@ -14,13 +16,21 @@ impl Plugin for MechaPickerPlugin {
         #[cfg(not(feature = "debug"))]
         {
             app.insert_resource(TextDisplayTimers::default())
-                .add_systems(OnEnter(AppState::MechaPicker), setup)
+                .add_systems(
+                    OnEnter(AppState::MechaPicker),
+                    setup.run_if(not(skip_animation)),
+                )
                 .add_event::<PickMechaEvent>()
                 .add_systems(OnExit(AppState::MechaPicker), cleanup::<CleanupMarker>)
                 .add_systems(
                     Update,
                     (pick_mecha, animate_mecha_selection, handle_mecha_pick_event)
-                        .run_if(in_state(AppState::MechaPicker)),
+                        .run_if(in_state(AppState::MechaPicker).and_then(not(skip_animation))),
+                )
+                .add_systems(
+                    Update,
+                    pick_mecha_instantly
+                        .run_if(in_state(AppState::MechaPicker).and_then(skip_animation)),
                 );
         }
     }
@@ -60,6 +70,53 @@ fn skip_picking(
     next_ingame_state.set(IngameState::InGame);
 }
 
+// replays go straight to the round with the recorded mechaburro
+fn skip_animation(
+    launch_options: Res<cli::LaunchOptions>,
+    replay: Option<Res<replay::ReplayPlayback>>,
+) -> bool {
+    launch_options.skip_cutscenes || replay.is_some()
+}
+
+fn pick_mecha_instantly(
+    mut next_state: ResMut<NextState<AppState>>,
+    mut next_ingame_state: ResMut<NextState<IngameState>>,
+    game_assets: Res<assets::GameAssets>,
+    mut burros: Query<(Entity, &mut burro::Burro)>,
+    mut burro_meshes: Query<(
+        &mut Handle<ToonShaderMaterial>,
+        &mut OutlineVolume,
+        &burro::BurroMeshMarker,
+    )>,
+    mut game_rng: ResMut<rng::GameRng>,
+    replay: Option<Res<replay::ReplayPlayback>>,
+) {
+    // the burro meshes are hooked up once their scenes finish spawning
+    if burro_meshes.iter().count() < burros.iter().count() {
+        return;
+    }
+
+    let mechaburro = match replay.and_then(|replay| replay.mechaburro()) {
+        Some(selected_burro) => burros
+            .iter_mut()
+            .find(|(_, burro)| burro.selected_burro == selected_burro),
+        None => burros.iter_mut().choose(&mut game_rng.gameplay),
+    };
+    if let Some((entity, mut burro)) = mechaburro {
+        for (mut toon_material, mut outline_volume, _) in burro_meshes
+            .iter_mut()
+            .filter(|(_, _, m)| m.parent == Some(entity))
+        {
+            *toon_material = game_assets.mechaburro_texture.toon_texture.clone();
+            outline_volume.colour = Color::RED;
+        }
+        burro.is_mechaburro = true;
+    }
+
+    next_state.set(AppState::InGame);
+    next_ingame_state.set(IngameState::InGame);
+}
+
 #[derive(PartialEq, Debug)]
 enum MechaSelectionStage {
     Initial,
@@ -91,6 +148,7 @@ fn animate_mecha_selection(
     )>,
     mut camera_settings: ResMut<game_camera::CameraSettings>,
     mut bullet_event_writer: EventWriter<bullet::BulletEvent>,
+    game_config: Res<config::GameConfiguration>,
     top_texts: Query<Entity, With<TopTextMarker>>,
     name_texts: Query<Entity, With<TextMarker>>,
 ) {
@@ -139,14 +197,17 @@ fn animate_mecha_selection(
                     }
                 }
                 MechaSelectionStage::LaserShot => {
-                    bullet_event_writer.send(bullet::BulletEvent {
-                        source: selected_burro_entity,
-                        speed: burro.bullet_speed,
-                        time_to_live: burro.bullet_time_alive,
-                        position: transform.translation,
-                        direction: Vec3::new(1.0, 0.0, 0.0),
-                        bullet_type: bullet::BulletType::Laser,
-                    });
+                    if let Some(weapon) = game_assets
+                        .weapons
+                        .get(game_config.weapon_name(burro.is_mechaburro))
+                    {
+                        bullet_event_writer.send(bullet::BulletEvent {
+                            source: selected_burro_entity,
+                            position: transform.translation,
+                            direction: Vec3::new(1.0, 0.0, 0.0),
+                            weapon: weapon.clone(),
+                        });
+                    }
                     transform.rotation = Quat::from_axis_angle(Vec3::Y, 0.0);
                     transform.scale = Vec3::new(0.7, 1.4, 1.0);
                 }
@@ -257,6 +318,7 @@ fn pick_mecha(
     mut pick_mecha_event_writer: EventWriter<PickMechaEvent>,
     game_assets: Res<assets::GameAssets>,
     game_state: Res<game_state::GameState>,
+    mut game_rng: ResMut<rng::GameRng>,
 ) {
     if text_display_timers.has_picked {
         text_display_timers.mecha_display_cooldown -= time.delta_seconds();
@@ -278,10 +340,9 @@ fn pick_mecha(
         return;
     }
 
-    let mut rng = thread_rng();
     if text_display_timers.overall_name_selection_cooldown < 0.0 {
         // select mechaburro
-        if let Some(choice) = game_state.burros.choose(&mut rng) {
+        if let Some(choice) = game_state.burros.choose(&mut game_rng.gameplay) {
             pick_mecha_event_writer.send(PickMechaEvent {
                 selected_burro: choice.selected_burro,
             });
@@ -301,7 +362,7 @@ fn pick_mecha(
             .collect::<Vec<_>>();
 
         for mut text in texts.iter_mut() {
-            if let Some(choice) = current_burros.choose(&mut rng) {
+            if let Some(choice) = current_burros.choose(&mut game_rng.cosmetic) {
                 let burro_name = game_assets.burro_assets[*choice].name.clone();
                 text.sections[0].value = burro_name;
             }
@@ -19,6 +19,9 @@ impl Command for CharacterSelectLoader {
         assets_handler.add_font(&mut game_assets.score_font, "fonts/monogram.ttf");
         assets_handler.add_glb(&mut game_assets.burro, "models/burro_new.glb");
 
+        // the folder is read again each time so the burros aren't listed twice
+        game_assets.burro_assets.clear();
+
         let folder_path = "assets/textures/burros";
         if let Ok(entries) = fs::read_dir(folder_path) {
             for entry in entries {
@@ -57,8 +60,11 @@ impl Command for CharacterSelectLoader {
                     }
                 }
             }
-        } else {
-            panic!("Assets folder or burros folder not found, can't run the game");
+        }
+
+        // a missing or empty folder leaves nobody to play as
+        if game_assets.burro_assets.is_empty() {
+            assets_handler.add_failure("textures/burros");
         }
     }
 }
@@ -56,7 +56,7 @@ pub struct CleanupMarker;
 
 const COLOR: f32 = 255.;
 const COLOR_COUNT: usize = 24;
-const OUTLINE_COLORS: [Color; COLOR_COUNT] = [
+pub const OUTLINE_COLORS: [Color; COLOR_COUNT] = [
     Color::rgb(112. / COLOR, 214. / COLOR, 255. / COLOR),
     Color::rgb(255. / COLOR, 251. / COLOR, 204. / COLOR),
     Color::rgb(90. / COLOR, 232. / COLOR, 110. / COLOR),
@@ -269,10 +269,10 @@ fn handle_input(
     game_assets: Res<assets::GameAssets>,
     mut audio: audio::GameAudio,
     mut player_selection: ResMut<PlayerSelection>,
+    keys: Res<Input<KeyCode>>,
     time: Res<Time>,
 
     #[cfg(feature = "debug")] mut selected_player: Local<usize>,
-    #[cfg(feature = "debug")] keys: Res<Input<KeyCode>>,
     #[cfg(feature = "debug")] mut game_state: ResMut<game_state::GameState>,
 ) {
     #[cfg(feature = "debug")]
@@ -355,6 +355,7 @@ fn handle_input(
                 if action_state.just_pressed(input::MenuAction::Select) {
                     play_audio = true;
                     player_selection.state = SelectionState::Burro;
+                    player_selection.input_device = input::InputDevice::detect(player.0, &keys);
                 }
 
                 if action_state.just_pressed(input::MenuAction::Back) && playing_count == 0 {
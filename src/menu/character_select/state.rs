@@ -1,4 +1,4 @@
-use crate::game_state;
+use crate::{game_state, input};
 use bevy::prelude::*;
 
 #[derive(Component, Clone, Default)]
@@ -7,6 +7,7 @@ pub struct PlayerSelectionState {
     pub outline_color: usize,
     pub state: SelectionState,
     pub axis_cooldown: Timer,
+    pub input_device: input::InputDevice,
 }
 
 impl PlayerSelectionState {
@@ -23,7 +24,9 @@ impl From<(PlayerSelectionState, game_state::PlayerMarker)> for game_state::Burr
             outline_color: item.0.get_outline_color(),
             score: 0,
             is_bot: false,
+            bot_profile: None,
             hearts: vec![],
+            input_device: item.0.input_device,
         }
     }
 }
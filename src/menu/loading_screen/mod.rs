@@ -0,0 +1,182 @@
+use crate::asset_loading::AssetsLoading;
+use crate::input::InputCommandsExt;
+use crate::{cleanup, game_camera, menu::MenuOption, ui, AppState};
+use bevy::prelude::*;
+
+mod state;
+mod update;
+
+use self::{
+    state::{LoadingErrorOptions, LoadingErrorState},
+    update::{handle_input, highlight_selection, update_progress},
+};
+
+// the game's fonts might be what's loading or what failed so these screens use bevy's own
+const MESSAGE_FONT_SIZE: f32 = ui::DEFAULT_FONT_SIZE * 0.8;
+const PATH_FONT_SIZE: f32 = ui::DEFAULT_FONT_SIZE * 0.5;
+
+pub struct LoadingScreenPlugin;
+impl Plugin for LoadingScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadingErrorState>()
+            .add_systems(OnEnter(AppState::Loading), setup_loading)
+            .add_systems(Update, update_progress.run_if(in_state(AppState::Loading)))
+            .add_systems(OnExit(AppState::Loading), cleanup::<CleanupMarker>)
+            .add_systems(OnEnter(AppState::LoadingError), setup_error)
+            .add_systems(
+                Update,
+                (highlight_selection, handle_input).run_if(in_state(AppState::LoadingError)),
+            )
+            .add_systems(OnExit(AppState::LoadingError), cleanup::<CleanupMarker>);
+    }
+}
+
+#[derive(Component)]
+struct CleanupMarker;
+
+#[derive(Component)]
+struct ProgressBar;
+
+fn setup_loading(mut commands: Commands, text_scaler: ui::text_size::TextScaler) {
+    game_camera::spawn_camera(&mut commands, CleanupMarker);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            CleanupMarker,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Loading",
+                TextStyle {
+                    font_size: text_scaler.scale(MESSAGE_FONT_SIZE),
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(50.0),
+                        height: Val::Percent(3.0),
+                        margin: UiRect::top(Val::Percent(2.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.2, 0.2, 0.2).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(0.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            background_color: ui::HOVERED_BUTTON.into(),
+                            ..default()
+                        },
+                        ProgressBar,
+                    ));
+                });
+        });
+}
+
+fn setup_error(
+    mut commands: Commands,
+    assets_loading: Res<AssetsLoading>,
+    mut error_state: ResMut<LoadingErrorState>,
+    text_scaler: ui::text_size::TextScaler,
+) {
+    error_state.selected_option = LoadingErrorOptions::default();
+    game_camera::spawn_camera(&mut commands, CleanupMarker);
+    commands.spawn_menu_input(CleanupMarker);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            CleanupMarker,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "Some files couldn't be loaded",
+                    TextStyle {
+                        font_size: text_scaler.scale(MESSAGE_FONT_SIZE),
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Percent(2.0)),
+                    ..default()
+                }),
+            );
+
+            for path in assets_loading.failed.iter() {
+                parent.spawn(TextBundle::from_section(
+                    format!("assets/{}", path),
+                    TextStyle {
+                        font_size: text_scaler.scale(PATH_FONT_SIZE),
+                        color: Color::rgb(1.0, 0.5, 0.5),
+                        ..default()
+                    },
+                ));
+            }
+
+            for option in LoadingErrorOptions::get() {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Percent(25.0),
+                                height: Val::Percent(10.0),
+                                margin: UiRect::top(Val::Percent(2.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: ui::NORMAL_BUTTON.into(),
+                            ..default()
+                        },
+                        option,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            TextBundle::from_section(
+                                option.get_label(),
+                                TextStyle {
+                                    font_size: text_scaler.scale(ui::BUTTON_LABEL_FONT_SIZE),
+                                    color: Color::WHITE,
+                                    ..default()
+                                },
+                            ),
+                            option,
+                        ));
+                    });
+            }
+        });
+}
@@ -0,0 +1,25 @@
+use crate::menu::MenuOption;
+use bevy::prelude::*;
+
+#[derive(Default, Resource)]
+pub struct LoadingErrorState {
+    pub selected_option: LoadingErrorOptions,
+}
+
+#[derive(Component, Copy, Clone, PartialEq, Default)]
+pub enum LoadingErrorOptions {
+    #[default]
+    Retry,
+    Title,
+}
+
+impl MenuOption<2> for LoadingErrorOptions {
+    const ITEM: [LoadingErrorOptions; 2] = [LoadingErrorOptions::Retry, LoadingErrorOptions::Title];
+
+    fn get_label(&self) -> &str {
+        match self {
+            LoadingErrorOptions::Retry => "Retry",
+            LoadingErrorOptions::Title => "Title Screen",
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use super::state::{LoadingErrorOptions, LoadingErrorState};
+use super::ProgressBar;
+use crate::asset_loading::{AssetsLoading, QueueState};
+use crate::loading::command_ext::*;
+use crate::{input, menu, ui, AppState};
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use menu::MenuOption;
+
+pub fn update_progress(
+    assets_loading: Res<AssetsLoading>,
+    asset_server: Res<AssetServer>,
+    mut progress_bars: Query<&mut Style, With<ProgressBar>>,
+) {
+    let progress = assets_loading.progress(&asset_server);
+    for mut style in &mut progress_bars {
+        style.width = Val::Percent(progress * 100.0);
+    }
+}
+
+pub fn highlight_selection(
+    error_state: Res<LoadingErrorState>,
+    mut options: Query<(
+        &LoadingErrorOptions,
+        Option<&mut BackgroundColor>,
+        Option<&mut Text>,
+    )>,
+) {
+    for (&option, maybe_background_color, maybe_text) in &mut options {
+        let (background_color, text_color) = if option == error_state.selected_option {
+            (ui::HOVERED_BUTTON, Color::BLACK)
+        } else {
+            (ui::NORMAL_BUTTON, Color::WHITE)
+        };
+
+        if let Some(mut background) = maybe_background_color {
+            *background = BackgroundColor(background_color);
+        }
+        if let Some(mut text) = maybe_text {
+            for text_section in text.sections.iter_mut() {
+                text_section.style.color = text_color;
+            }
+        }
+    }
+}
+
+pub fn handle_input(
+    mut commands: Commands,
+    mut error_state: ResMut<LoadingErrorState>,
+    action_state: Query<&ActionState<input::MenuAction>>,
+    mut assets_loading: ResMut<AssetsLoading>,
+    asset_server: Res<AssetServer>,
+    queued_state: Res<QueueState>,
+) {
+    let action_state = action_state.single();
+
+    if action_state.just_pressed(input::MenuAction::Up) {
+        error_state.selected_option = error_state.selected_option.previous();
+    }
+
+    if action_state.just_pressed(input::MenuAction::Down) {
+        error_state.selected_option = error_state.selected_option.next();
+    }
+
+    if action_state.just_pressed(input::MenuAction::Select) {
+        match error_state.selected_option {
+            LoadingErrorOptions::Retry => {
+                // the state's loader runs again too, it may have failed before queueing anything
+                assets_loading.retry(&asset_server);
+                commands.load_state(queued_state.state);
+            }
+            LoadingErrorOptions::Title => {
+                assets_loading.clear();
+                commands.load_state(AppState::TitleScreen);
+            }
+        }
+    }
+}
@@ -1,4 +1,6 @@
 pub mod character_select;
+pub mod loading_screen;
+pub mod pause;
 pub mod settings;
 pub mod splash;
 pub mod title_screen;
@@ -0,0 +1,232 @@
+use crate::{
+    assets::GameAssets,
+    audio, bot, cleanup, game_state, input,
+    menu::{self, settings::state::SettingsMenuState, MenuOption},
+    player, ui, AppState, IngameState,
+};
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+mod state;
+mod update;
+
+use self::{
+    state::{PauseMenuState, PauseOptions, PauseSettings},
+    update::{
+        handle_input, handle_settings_input, highlight_selection, show_page, update_setting_values,
+    },
+};
+
+pub struct PausePlugin;
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PauseMenuState>()
+            .add_systems(
+                Update,
+                open_pause_menu
+                    .run_if(in_state(AppState::InGame).and_then(in_state(IngameState::InGame))),
+            )
+            .add_systems(OnEnter(AppState::Pause), (setup, pause_animations))
+            .add_systems(
+                Update,
+                (
+                    highlight_selection,
+                    show_page.run_if(resource_changed::<PauseMenuState>()),
+                    update_setting_values,
+                    menu::settings::apply_audio_settings,
+                    // the options go first, otherwise the Back that closes the settings
+                    // would also resume the round
+                    (
+                        handle_input
+                            .run_if(|pause_state: Res<PauseMenuState>| !pause_state.settings_open),
+                        handle_settings_input
+                            .run_if(|pause_state: Res<PauseMenuState>| pause_state.settings_open),
+                    )
+                        .chain(),
+                )
+                    .run_if(in_state(AppState::Pause)),
+            )
+            .add_systems(OnExit(AppState::Pause), cleanup::<CleanupMarker>);
+    }
+}
+
+#[derive(Component)]
+struct CleanupMarker;
+#[derive(Component)]
+struct SettingValueMarker;
+
+const BACKGROUND_COLOR: Color = Color::rgba(0., 0., 0., 0.6);
+
+fn open_pause_menu(
+    players: Query<&ActionState<player::PlayerAction>, Without<bot::Bot>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if players
+        .iter()
+        .any(|action_state| action_state.just_pressed(player::PlayerAction::Pause))
+    {
+        next_state.set(AppState::Pause);
+    }
+}
+
+// move_player resumes these every frame once the round is running again
+fn pause_animations(mut animations: Query<&mut AnimationPlayer>) {
+    for mut animation in &mut animations {
+        animation.pause();
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    game_state: Res<game_state::GameState>,
+    mut pause_state: ResMut<PauseMenuState>,
+    mut settings_menu: ResMut<SettingsMenuState>,
+    audio_settings: Res<audio::AudioSettings>,
+    text_scaler: ui::text_size::TextScaler,
+) {
+    *pause_state = PauseMenuState::default();
+    // the volumes are changed through the settings menu's state
+    settings_menu.load_audio(&audio_settings);
+
+    // every human in the match can drive the menu with the device they joined with
+    for burro in game_state.burros.iter().filter(|burro| !burro.is_bot) {
+        commands.spawn((
+            input::create_menu_input_for_device(burro.input_device),
+            CleanupMarker,
+        ));
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: BACKGROUND_COLOR.into(),
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+            CleanupMarker,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle {
+                style: Style {
+                    margin: UiRect {
+                        bottom: Val::Percent(2.5),
+                        ..default()
+                    },
+                    ..default()
+                },
+                text: Text::from_section(
+                    "Pausa",
+                    TextStyle {
+                        font: game_assets.font.clone(),
+                        font_size: text_scaler.scale(ui::DEFAULT_FONT_SIZE * 1.2),
+                        color: Color::WHITE,
+                    },
+                ),
+                ..default()
+            });
+
+            PauseOptions::get().into_iter().for_each(|option| {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                position_type: PositionType::Relative,
+                                width: Val::Percent(25.0),
+                                height: Val::Percent(10.0),
+                                margin: UiRect::all(Val::Percent(0.5)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: ui::NORMAL_BUTTON.into(),
+                            ..default()
+                        },
+                        option,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            TextBundle {
+                                text: Text::from_section(
+                                    option.get_label(),
+                                    TextStyle {
+                                        font: game_assets.font.clone(),
+                                        font_size: text_scaler.scale(ui::BUTTON_LABEL_FONT_SIZE),
+                                        color: Color::WHITE,
+                                    },
+                                ),
+                                ..default()
+                            },
+                            option,
+                        ));
+                    });
+            });
+
+            // hidden until Settings is picked, see show_page
+            PauseSettings::get().into_iter().for_each(|setting| {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                display: Display::None,
+                                position_type: PositionType::Relative,
+                                width: Val::Percent(40.0),
+                                height: Val::Percent(10.0),
+                                margin: UiRect::all(Val::Percent(0.5)),
+                                padding: UiRect::horizontal(Val::Percent(2.0)),
+                                justify_content: JustifyContent::SpaceBetween,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: ui::NORMAL_BUTTON.into(),
+                            ..default()
+                        },
+                        setting,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            TextBundle {
+                                text: Text::from_section(
+                                    setting.get_label(),
+                                    TextStyle {
+                                        font: game_assets.font.clone(),
+                                        font_size: text_scaler.scale(ui::BUTTON_LABEL_FONT_SIZE),
+                                        color: Color::WHITE,
+                                    },
+                                ),
+                                ..default()
+                            },
+                            setting,
+                        ));
+
+                        if setting.setting().is_some() {
+                            parent.spawn((
+                                TextBundle {
+                                    text: Text::from_section(
+                                        "",
+                                        TextStyle {
+                                            font: game_assets.score_font.clone(),
+                                            font_size: text_scaler
+                                                .scale(ui::BUTTON_LABEL_FONT_SIZE),
+                                            color: Color::WHITE,
+                                        },
+                                    ),
+                                    ..default()
+                                },
+                                setting,
+                                SettingValueMarker,
+                            ));
+                        }
+                    });
+            });
+        });
+}
@@ -0,0 +1,73 @@
+use crate::menu::{settings::state::Settings, MenuOption};
+use bevy::prelude::*;
+
+#[derive(Default, Resource)]
+pub struct PauseMenuState {
+    pub selected_option: PauseOptions,
+    /// The settings are shown in place of the options, the round stays paused behind them
+    pub settings_open: bool,
+    pub selected_setting: PauseSettings,
+}
+
+#[derive(Component, Copy, Clone, PartialEq, Default)]
+pub enum PauseOptions {
+    #[default]
+    Resume,
+    RestartRound,
+    Settings,
+    QuitToTitle,
+}
+
+impl MenuOption<4> for PauseOptions {
+    const ITEM: [PauseOptions; 4] = [
+        PauseOptions::Resume,
+        PauseOptions::RestartRound,
+        PauseOptions::Settings,
+        PauseOptions::QuitToTitle,
+    ];
+
+    fn get_label(&self) -> &str {
+        match self {
+            PauseOptions::Resume => "Resume",
+            PauseOptions::RestartRound => "Restart Round",
+            PauseOptions::Settings => "Settings",
+            PauseOptions::QuitToTitle => "Quit to Title",
+        }
+    }
+}
+
+/// What can be changed without leaving the round, the rules stay as the match started
+#[derive(Component, Copy, Clone, PartialEq, Default)]
+pub enum PauseSettings {
+    #[default]
+    MusicVolume,
+    SoundVolume,
+    Back,
+}
+
+impl PauseSettings {
+    /// The settings menu entry this changes, the volumes are kept in its state
+    pub fn setting(&self) -> Option<Settings> {
+        match self {
+            PauseSettings::MusicVolume => Some(Settings::MusicVolume),
+            PauseSettings::SoundVolume => Some(Settings::SoundVolume),
+            PauseSettings::Back => None,
+        }
+    }
+}
+
+impl MenuOption<3> for PauseSettings {
+    const ITEM: [PauseSettings; 3] = [
+        PauseSettings::MusicVolume,
+        PauseSettings::SoundVolume,
+        PauseSettings::Back,
+    ];
+
+    fn get_label(&self) -> &str {
+        match self {
+            PauseSettings::MusicVolume => "Music Volume",
+            PauseSettings::SoundVolume => "Sound Volume",
+            PauseSettings::Back => "Back",
+        }
+    }
+}
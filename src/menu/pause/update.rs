@@ -0,0 +1,271 @@
+use super::state::{PauseMenuState, PauseOptions, PauseSettings};
+use super::SettingValueMarker;
+use crate::loading::command_ext::*;
+use crate::menu::settings::state::SettingsMenuState;
+use crate::{
+    assets, audio, bot, game_state, input, levels, menu, player, ui, AppState, IngameState,
+};
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use menu::MenuOption;
+
+pub fn highlight_selection(
+    pause_state: Res<PauseMenuState>,
+    mut options: Query<(
+        &PauseOptions,
+        Option<&mut BackgroundColor>,
+        Option<&mut Text>,
+    )>,
+    mut settings: Query<
+        (
+            &PauseSettings,
+            Option<&mut BackgroundColor>,
+            Option<&mut Text>,
+        ),
+        Without<PauseOptions>,
+    >,
+) {
+    for (&option, maybe_background_color, maybe_text) in &mut options {
+        highlight(
+            option == pause_state.selected_option,
+            maybe_background_color,
+            maybe_text,
+        );
+    }
+    for (&setting, maybe_background_color, maybe_text) in &mut settings {
+        highlight(
+            setting == pause_state.selected_setting,
+            maybe_background_color,
+            maybe_text,
+        );
+    }
+}
+
+fn highlight(
+    is_selected: bool,
+    maybe_background_color: Option<Mut<BackgroundColor>>,
+    maybe_text: Option<Mut<Text>>,
+) {
+    if is_selected {
+        if let Some(mut background_color) = maybe_background_color {
+            *background_color = BackgroundColor(ui::HOVERED_BUTTON);
+        }
+        if let Some(mut text) = maybe_text {
+            for text_section in text.sections.iter_mut() {
+                text_section.style.color = Color::BLACK;
+            }
+        }
+    } else {
+        if let Some(mut background_color) = maybe_background_color {
+            *background_color = BackgroundColor(ui::NORMAL_BUTTON);
+        }
+        if let Some(mut text) = maybe_text {
+            for text_section in text.sections.iter_mut() {
+                text_section.style.color = Color::WHITE;
+            }
+        }
+    }
+}
+
+/// Swaps the options for the settings and back
+pub fn show_page(
+    pause_state: Res<PauseMenuState>,
+    mut options: Query<&mut Style, With<PauseOptions>>,
+    mut settings: Query<&mut Style, (With<PauseSettings>, Without<PauseOptions>)>,
+) {
+    let (options_display, settings_display) = if pause_state.settings_open {
+        (Display::None, Display::Flex)
+    } else {
+        (Display::Flex, Display::None)
+    };
+
+    for mut style in &mut options {
+        style.display = options_display;
+    }
+    for mut style in &mut settings {
+        style.display = settings_display;
+    }
+}
+
+pub fn update_setting_values(
+    settings_menu: Res<SettingsMenuState>,
+    level_manifest: Res<levels::LevelManifest>,
+    mut values: Query<(&mut Text, &PauseSettings), With<SettingValueMarker>>,
+) {
+    for (mut text, setting) in &mut values {
+        if let Some(setting) = setting.setting() {
+            text.sections[0].value = settings_menu.display(&setting, &level_manifest);
+        }
+    }
+}
+
+pub fn handle_input(
+    mut commands: Commands,
+    mut pause_state: ResMut<PauseMenuState>,
+    action_states: Query<&ActionState<input::MenuAction>>,
+    players: Query<&ActionState<player::PlayerAction>, Without<bot::Bot>>,
+    game_assets: Res<assets::GameAssets>,
+    mut game_state: ResMut<game_state::GameState>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut next_ingame_state: ResMut<NextState<IngameState>>,
+    mut audio: audio::GameAudio,
+    mut axis_timer: Local<Timer>,
+    time: Res<Time>,
+) {
+    // the player entities keep their input history while paused so pressing
+    // pause again closes the menu without catching the press that opened it
+    if players
+        .iter()
+        .any(|action_state| action_state.just_pressed(player::PlayerAction::Pause))
+    {
+        audio.play_sfx(&game_assets.sfx_1);
+        next_state.set(AppState::InGame);
+        return;
+    }
+
+    axis_timer.tick(time.delta());
+    for action_state in &action_states {
+        if action_state.pressed(input::MenuAction::Move) && axis_timer.finished() {
+            let axis_pair = action_state
+                .clamped_axis_pair(input::MenuAction::Move)
+                .unwrap();
+            if axis_pair.y() == 1.0 {
+                audio.play_sfx(&game_assets.sfx_1);
+                pause_state.selected_option = pause_state.selected_option.previous();
+                *axis_timer = Timer::from_seconds(0.2, TimerMode::Once);
+            }
+            if axis_pair.y() == -1.0 {
+                audio.play_sfx(&game_assets.sfx_1);
+                pause_state.selected_option = pause_state.selected_option.next();
+                *axis_timer = Timer::from_seconds(0.2, TimerMode::Once);
+            }
+        }
+
+        if action_state.just_pressed(input::MenuAction::Up) {
+            audio.play_sfx(&game_assets.sfx_1);
+            pause_state.selected_option = pause_state.selected_option.previous();
+        }
+
+        if action_state.just_pressed(input::MenuAction::Down) {
+            audio.play_sfx(&game_assets.sfx_1);
+            pause_state.selected_option = pause_state.selected_option.next();
+        }
+
+        if action_state.just_pressed(input::MenuAction::Back) {
+            audio.play_sfx(&game_assets.sfx_1);
+            next_state.set(AppState::InGame);
+            return;
+        }
+
+        if action_state.just_pressed(input::MenuAction::Select) {
+            audio.play_sfx(&game_assets.sfx_1);
+            match pause_state.selected_option {
+                PauseOptions::Resume => next_state.set(AppState::InGame),
+                PauseOptions::RestartRound => {
+                    next_ingame_state.set(IngameState::Disabled);
+                    commands.load_state(AppState::LoadInGame);
+                }
+                PauseOptions::Settings => {
+                    pause_state.settings_open = true;
+                    pause_state.selected_setting = PauseSettings::default();
+                }
+                PauseOptions::QuitToTitle => {
+                    *game_state = game_state::GameState::default();
+                    next_ingame_state.set(IngameState::Disabled);
+                    commands.load_state(AppState::TitleScreen);
+                }
+            }
+            return;
+        }
+    }
+}
+
+pub fn handle_settings_input(
+    mut pause_state: ResMut<PauseMenuState>,
+    mut settings_menu: ResMut<SettingsMenuState>,
+    action_states: Query<&ActionState<input::MenuAction>>,
+    players: Query<&ActionState<player::PlayerAction>, Without<bot::Bot>>,
+    game_assets: Res<assets::GameAssets>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut audio: audio::GameAudio,
+    mut axis_timer: Local<Timer>,
+    time: Res<Time>,
+) {
+    if players
+        .iter()
+        .any(|action_state| action_state.just_pressed(player::PlayerAction::Pause))
+    {
+        audio.play_sfx(&game_assets.sfx_1);
+        next_state.set(AppState::InGame);
+        return;
+    }
+
+    axis_timer.tick(time.delta());
+    for action_state in &action_states {
+        // -1 or 1 to turn the selected volume down or up
+        let mut change = 0;
+        if action_state.pressed(input::MenuAction::Move) && axis_timer.finished() {
+            let axis_pair = action_state
+                .clamped_axis_pair(input::MenuAction::Move)
+                .unwrap();
+            if axis_pair.y() == 1.0 {
+                audio.play_sfx(&game_assets.sfx_1);
+                pause_state.selected_setting = pause_state.selected_setting.previous();
+                *axis_timer = Timer::from_seconds(0.2, TimerMode::Once);
+            }
+            if axis_pair.y() == -1.0 {
+                audio.play_sfx(&game_assets.sfx_1);
+                pause_state.selected_setting = pause_state.selected_setting.next();
+                *axis_timer = Timer::from_seconds(0.2, TimerMode::Once);
+            }
+            if axis_pair.x() == 1.0 {
+                change = 1;
+                *axis_timer = Timer::from_seconds(0.2, TimerMode::Once);
+            }
+            if axis_pair.x() == -1.0 {
+                change = -1;
+                *axis_timer = Timer::from_seconds(0.2, TimerMode::Once);
+            }
+        }
+
+        if action_state.just_pressed(input::MenuAction::Up) {
+            audio.play_sfx(&game_assets.sfx_1);
+            pause_state.selected_setting = pause_state.selected_setting.previous();
+        }
+
+        if action_state.just_pressed(input::MenuAction::Down) {
+            audio.play_sfx(&game_assets.sfx_1);
+            pause_state.selected_setting = pause_state.selected_setting.next();
+        }
+
+        if action_state.just_pressed(input::MenuAction::Left) {
+            change = -1;
+        }
+
+        if action_state.just_pressed(input::MenuAction::Right) {
+            change = 1;
+        }
+
+        // apply_audio_settings passes the new volume on
+        if let Some(setting) = pause_state.selected_setting.setting() {
+            if change != 0 {
+                audio.play_sfx(&game_assets.sfx_1);
+                settings_menu.selected_setting = setting;
+                if change > 0 {
+                    settings_menu.increment();
+                } else {
+                    settings_menu.decrement();
+                }
+            }
+        }
+
+        if action_state.just_pressed(input::MenuAction::Back)
+            || (action_state.just_pressed(input::MenuAction::Select)
+                && pause_state.selected_setting == PauseSettings::Back)
+        {
+            audio.play_sfx(&game_assets.sfx_1);
+            pause_state.settings_open = false;
+            return;
+        }
+    }
+}
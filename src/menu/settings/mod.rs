@@ -3,9 +3,10 @@ use bevy::prelude::*;
 
 pub mod loader;
 mod setup;
-mod state;
+pub mod state;
 mod update;
 
+pub use self::update::apply_audio_settings;
 use self::{
     setup::setup,
     state::SettingsMenuState,
@@ -19,7 +20,12 @@ impl Plugin for SettingsMenuPlugin {
             .init_resource::<SettingsMenuState>()
             .add_systems(
                 Update,
-                (highlight_selection, handle_input, update_values)
+                (
+                    highlight_selection,
+                    handle_input,
+                    update_values,
+                    apply_audio_settings,
+                )
                     .run_if(in_state(AppState::Settings)),
             )
             .add_systems(OnExit(AppState::Settings), cleanup::<CleanupMarker>);
@@ -1,19 +1,34 @@
 use super::state::{Settings, SettingsMenuState};
 use super::{CleanupMarker, SettingDisplayMarker};
 use crate::input::InputCommandsExt;
-use crate::{assets, game_camera, menu, menu::MenuOption, ui};
+use crate::{assets, audio, config, game_camera, levels, menu, menu::MenuOption, ui};
 use bevy::prelude::*;
 
+const ROW_FONT_SIZE: f32 = ui::DEFAULT_FONT_SIZE * 0.6;
+
 pub fn setup(
     mut commands: Commands,
     game_assets: Res<assets::GameAssets>,
     text_scaler: ui::text_size::TextScaler,
     mut setting_state: ResMut<SettingsMenuState>,
     player_selection: Res<menu::character_select::state::PlayerSelection>,
+    match_rules: Res<config::MatchRules>,
+    audio_settings: Res<audio::AudioSettings>,
+    level_manifest: Res<levels::LevelManifest>,
 ) {
-    *setting_state = SettingsMenuState::default();
+    // bots, their profiles and unfair advantage are kept from the last time the menu was used
+    setting_state.selected_setting = Settings::default();
+    setting_state.bot_slot = 0;
     setting_state.number_of_players = player_selection.players.len() as isize;
-    setting_state.number_of_bots = setting_state.min_bots();
+    setting_state.number_of_bots = setting_state
+        .number_of_bots
+        .clamp(setting_state.min_bots(), setting_state.max_bots());
+    setting_state.number_of_levels = level_manifest.count() as isize;
+    setting_state.starting_level = setting_state
+        .starting_level
+        .clamp(0, setting_state.number_of_levels - 1);
+    setting_state.load_rules(&match_rules);
+    setting_state.load_audio(&audio_settings);
     game_camera::spawn_camera(&mut commands, CleanupMarker);
     commands.spawn_menu_input(CleanupMarker);
 
@@ -40,7 +55,7 @@ pub fn setup(
         .spawn(NodeBundle {
             style: Style {
                 width: Val::Percent(100.),
-                height: Val::Percent(20.),
+                height: Val::Percent(10.),
                 display: Display::Flex,
                 justify_content: JustifyContent::Center,
                 ..default()
@@ -70,11 +85,11 @@ pub fn setup(
                     NodeBundle {
                         style: Style {
                             width: Val::Percent(20.),
-                            height: Val::Percent(15.),
+                            height: Val::Percent(6.0),
                             display: Display::Flex,
-                            padding: UiRect::all(Val::Percent(2.)),
+                            padding: UiRect::all(Val::Percent(1.)),
                             margin: UiRect {
-                                top: Val::Percent(15.),
+                                top: Val::Percent(2.),
                                 ..default()
                             },
                             align_items: AlignItems::Center,
@@ -96,7 +111,7 @@ pub fn setup(
                                 format!("{}", setting.get_label()),
                                 TextStyle {
                                     font: game_assets.score_font.clone(),
-                                    font_size: text_scaler.scale(ui::DEFAULT_FONT_SIZE),
+                                    font_size: text_scaler.scale(ROW_FONT_SIZE),
                                     color: Color::WHITE,
                                 },
                             ),
@@ -111,9 +126,9 @@ pub fn setup(
                     NodeBundle {
                         style: Style {
                             width: Val::Percent(100.),
-                            height: Val::Percent(15.),
+                            height: Val::Percent(5.0),
                             display: Display::Flex,
-                            padding: UiRect::all(Val::Percent(2.)),
+                            padding: UiRect::all(Val::Percent(1.)),
                             align_items: AlignItems::Center,
                             justify_content: JustifyContent::SpaceBetween,
                             flex_direction: FlexDirection::Row,
@@ -130,7 +145,7 @@ pub fn setup(
                                 format!("{}:", setting.get_label()),
                                 TextStyle {
                                     font: game_assets.font.clone(),
-                                    font_size: text_scaler.scale(ui::DEFAULT_FONT_SIZE),
+                                    font_size: text_scaler.scale(ROW_FONT_SIZE),
                                     color: Color::WHITE,
                                 },
                             ),
@@ -159,7 +174,7 @@ pub fn setup(
                                         "<",
                                         TextStyle {
                                             font: game_assets.score_font.clone(),
-                                            font_size: text_scaler.scale(ui::DEFAULT_FONT_SIZE),
+                                            font_size: text_scaler.scale(ROW_FONT_SIZE),
                                             color: Color::WHITE,
                                         },
                                     ),
@@ -173,7 +188,7 @@ pub fn setup(
                                         "5",
                                         TextStyle {
                                             font: game_assets.score_font.clone(),
-                                            font_size: text_scaler.scale(ui::DEFAULT_FONT_SIZE),
+                                            font_size: text_scaler.scale(ROW_FONT_SIZE),
                                             color: Color::WHITE,
                                         },
                                     ),
@@ -188,7 +203,7 @@ pub fn setup(
                                         ">",
                                         TextStyle {
                                             font: game_assets.score_font.clone(),
-                                            font_size: text_scaler.scale(ui::DEFAULT_FONT_SIZE),
+                                            font_size: text_scaler.scale(ROW_FONT_SIZE),
                                             color: Color::WHITE,
                                         },
                                     ),
@@ -1,28 +1,222 @@
 use crate::util::num_ext::*;
-use crate::{config, menu::MenuOption};
+use crate::{audio, config, levels, menu::MenuOption, player};
 use bevy::prelude::*;
 
+// scales like fire cooldown and bullet range move in quarter steps
+const SCALE_STEP: f32 = 0.25;
+// durations like being down move in half second steps
+const DURATION_STEP: f32 = 0.5;
+// volumes go from 0 to 10
+const VOLUME_STEP: f64 = 0.1;
+
 #[derive(Default, Resource)]
 pub struct SettingsMenuState {
     pub selected_setting: Settings,
     pub number_of_players: isize,
     pub number_of_bots: isize,
+    pub bot_skill: isize,
+    /// Which bot the Bot Profile row shows, counting from 0
+    pub bot_slot: isize,
+    /// The profile each bot plays with, one for every slot a bot could fill
+    pub bot_profiles: Vec<String>,
+    /// The names of the profiles in `assets/bots` the Bot Profile row cycles through
+    pub profile_names: Vec<String>,
     pub unfair_advantage: isize,
+    pub aim_mode: isize,
+    pub number_of_levels: isize,
+    pub starting_level: isize,
+    pub rounds: isize,
+    pub starting_hearts: isize,
+    pub fire_cooldown: isize,
+    pub down_cooldown: isize,
+    pub invulnerability_cooldown: isize,
+    pub bullet_range: isize,
+    pub music_volume: isize,
+    pub sound_volume: isize,
 }
 
 impl SettingsMenuState {
-    pub fn display(&self, setting: &Settings) -> String {
+    pub fn display(&self, setting: &Settings, level_manifest: &levels::LevelManifest) -> String {
         match setting {
             Settings::NumberOfBots => format!("{}", self.number_of_bots),
+            Settings::BotSkill if !self.is_bot_skill_preset() => "Custom".to_string(),
+            Settings::BotSkill => match self.bot_skill {
+                0 => "Easy".to_string(),
+                1 => "Normal".to_string(),
+                _ => "Hard".to_string(),
+            },
+            Settings::BotSlot if self.number_of_bots == 0 => "-".to_string(),
+            Settings::BotSlot => format!("{}", self.bot_slot + 1),
+            Settings::BotProfile if self.number_of_bots == 0 => "-".to_string(),
+            Settings::BotProfile => {
+                let name = self.slot_profile(self.bot_slot.max(0) as usize);
+                let mut letters = name.chars();
+                letters.next().map_or_else(String::new, |first| {
+                    first.to_uppercase().chain(letters).collect()
+                })
+            }
             Settings::UnfairAdvantage => match self.unfair_advantage {
                 0 => "Mechaburrito".to_string(),
                 1 => " Mechaburro ".to_string(),
                 _ => "Mechagigante".to_string(),
             },
+            Settings::AimMode => match self.aim_mode() {
+                player::AimMode::Cardinal => "4-Way".to_string(),
+                player::AimMode::EightWay => "8-Way".to_string(),
+                player::AimMode::Free => "Free".to_string(),
+            },
+            Settings::StartingLevel => level_manifest
+                .level(self.starting_level as usize)
+                .name
+                .clone(),
+            Settings::Rounds => format!("{}", self.rounds),
+            Settings::StartingHearts => format!("{}", self.starting_hearts),
+            Settings::FireCooldown => format!("x{:.2}", self.fire_cooldown as f32 * SCALE_STEP),
+            Settings::DownCooldown => {
+                format!("{:.1}s", self.down_cooldown as f32 * DURATION_STEP)
+            }
+            Settings::InvulnerabilityCooldown => {
+                format!(
+                    "{:.1}s",
+                    self.invulnerability_cooldown as f32 * DURATION_STEP
+                )
+            }
+            Settings::BulletRange => format!("x{:.2}", self.bullet_range as f32 * SCALE_STEP),
+            Settings::MusicVolume => format!("{}", self.music_volume),
+            Settings::SoundVolume => format!("{}", self.sound_volume),
             setting => setting.get_label().to_string(),
         }
     }
 
+    pub fn aim_mode(&self) -> player::AimMode {
+        match self.aim_mode {
+            0 => player::AimMode::Cardinal,
+            1 => player::AimMode::EightWay,
+            _ => player::AimMode::Free,
+        }
+    }
+
+    /// The profile the bot skill puts every bot on, see `assets/bots`
+    pub fn bot_profile(&self) -> &'static str {
+        match self.bot_skill {
+            0 => "easy",
+            1 => "normal",
+            _ => "hard",
+        }
+    }
+
+    /// The profile the bot in `slot` plays with
+    pub fn slot_profile(&self, slot: usize) -> &str {
+        self.bot_profiles
+            .get(slot)
+            .map_or(self.bot_profile(), |profile| profile.as_str())
+    }
+
+    // whether every bot in the match is on the bot skill's profile
+    fn is_bot_skill_preset(&self) -> bool {
+        (0..self.number_of_bots.max(0) as usize)
+            .all(|slot| self.slot_profile(slot) == self.bot_profile())
+    }
+
+    /// Fills in each bot slot's profile from the saved ones, slots without one or with one
+    /// that isn't in `profile_names` get the bot skill's. Returns the saved names that
+    /// weren't found
+    pub fn load_bot_profiles(
+        &mut self,
+        saved: &[String],
+        profile_names: Vec<String>,
+    ) -> Vec<String> {
+        let mut missing = vec![];
+        self.bot_profiles = (0..config::MAX_NUMBER_OF_PLAYERS as usize)
+            .map(|slot| match saved.get(slot) {
+                Some(profile) if profile_names.contains(profile) => profile.clone(),
+                Some(profile) => {
+                    missing.push(profile.clone());
+                    self.bot_profile().to_string()
+                }
+                None => self.bot_profile().to_string(),
+            })
+            .collect();
+        self.profile_names = profile_names;
+
+        missing
+    }
+
+    // steps the shown bot's profile through the profiles in `assets/bots`
+    fn step_bot_profile(&mut self, step: isize) {
+        let slot = self.bot_slot.max(0) as usize;
+        if self.number_of_bots == 0
+            || self.profile_names.is_empty()
+            || slot >= self.bot_profiles.len()
+        {
+            return;
+        }
+
+        let count = self.profile_names.len() as isize;
+        let next = match self
+            .profile_names
+            .iter()
+            .position(|name| *name == self.bot_profiles[slot])
+        {
+            Some(current) => (current as isize + step).rem_euclid(count),
+            None => 0,
+        };
+        self.bot_profiles[slot] = self.profile_names[next as usize].clone();
+    }
+
+    // after a step the bot skill puts every bot on its profile and the shown bot
+    // has to still be in the match
+    fn after_step(&mut self) {
+        if self.selected_setting == Settings::BotSkill {
+            let profile = self.bot_profile().to_string();
+            self.bot_profiles
+                .iter_mut()
+                .for_each(|slot| *slot = profile.clone());
+        }
+
+        self.bot_slot = self.bot_slot.clamp(0, (self.number_of_bots - 1).max(0));
+    }
+
+    /// Fills in the rule settings from the last rules that were played
+    pub fn load_rules(&mut self, rules: &config::MatchRules) {
+        self.rounds = rules.rounds as isize;
+        self.starting_hearts = rules.starting_hearts as isize;
+        self.fire_cooldown = (rules.fire_cooldown_scale / SCALE_STEP).round() as isize;
+        self.down_cooldown = (rules.down_cooldown / DURATION_STEP).round() as isize;
+        self.invulnerability_cooldown =
+            (rules.invulnerability_cooldown / DURATION_STEP).round() as isize;
+        self.bullet_range = (rules.bullet_range_scale / SCALE_STEP).round() as isize;
+        self.aim_mode = match rules.aim_mode {
+            player::AimMode::Cardinal => 0,
+            player::AimMode::EightWay => 1,
+            player::AimMode::Free => 2,
+        };
+    }
+
+    pub fn match_rules(&self) -> config::MatchRules {
+        config::MatchRules {
+            rounds: self.rounds as usize,
+            starting_hearts: self.starting_hearts as usize,
+            fire_cooldown_scale: self.fire_cooldown as f32 * SCALE_STEP,
+            down_cooldown: self.down_cooldown as f32 * DURATION_STEP,
+            invulnerability_cooldown: self.invulnerability_cooldown as f32 * DURATION_STEP,
+            bullet_range_scale: self.bullet_range as f32 * SCALE_STEP,
+            aim_mode: self.aim_mode(),
+        }
+    }
+
+    pub fn load_audio(&mut self, audio_settings: &audio::AudioSettings) {
+        self.music_volume = (audio_settings.music_volume / VOLUME_STEP).round() as isize;
+        self.sound_volume = (audio_settings.sound_volume / VOLUME_STEP).round() as isize;
+    }
+
+    pub fn audio_settings(&self) -> audio::AudioSettings {
+        audio::AudioSettings {
+            music_volume: self.music_volume as f64 * VOLUME_STEP,
+            sound_volume: self.sound_volume as f64 * VOLUME_STEP,
+        }
+    }
+
     pub fn max_bots(&self) -> isize {
         config::MAX_NUMBER_OF_PLAYERS - self.number_of_players
     }
@@ -34,32 +228,78 @@ impl SettingsMenuState {
         }
     }
 
-    pub fn increment(&mut self) {
-        match self.selected_setting {
-            Settings::NumberOfBots => {
-                self.number_of_bots = self
-                    .number_of_bots
-                    .circular_increment(self.min_bots(), self.max_bots());
-            }
-            Settings::UnfairAdvantage => {
-                self.unfair_advantage = self.unfair_advantage.circular_increment(0, 2);
+    /// The lowest and highest step of each setting, settings read from disk are held to these too
+    fn range(&self, setting: Settings) -> (isize, isize) {
+        match setting {
+            Settings::NumberOfBots => (self.min_bots(), self.max_bots()),
+            Settings::BotSkill | Settings::UnfairAdvantage | Settings::AimMode => (0, 2),
+            Settings::BotSlot => (0, (self.number_of_bots - 1).max(0)),
+            Settings::StartingLevel => (0, self.number_of_levels - 1),
+            Settings::Rounds => (1, 14),
+            Settings::StartingHearts => (1, 5),
+            Settings::FireCooldown | Settings::BulletRange => (2, 8),
+            Settings::DownCooldown | Settings::InvulnerabilityCooldown => (1, 6),
+            Settings::MusicVolume | Settings::SoundVolume => (0, 10),
+            Settings::BotProfile | Settings::Vamos => (0, 0),
+        }
+    }
+
+    fn value_mut(&mut self, setting: Settings) -> Option<&mut isize> {
+        match setting {
+            Settings::NumberOfBots => Some(&mut self.number_of_bots),
+            Settings::BotSkill => Some(&mut self.bot_skill),
+            Settings::BotSlot => Some(&mut self.bot_slot),
+            Settings::UnfairAdvantage => Some(&mut self.unfair_advantage),
+            Settings::AimMode => Some(&mut self.aim_mode),
+            Settings::StartingLevel => Some(&mut self.starting_level),
+            Settings::Rounds => Some(&mut self.rounds),
+            Settings::StartingHearts => Some(&mut self.starting_hearts),
+            Settings::FireCooldown => Some(&mut self.fire_cooldown),
+            Settings::DownCooldown => Some(&mut self.down_cooldown),
+            Settings::InvulnerabilityCooldown => Some(&mut self.invulnerability_cooldown),
+            Settings::BulletRange => Some(&mut self.bullet_range),
+            Settings::MusicVolume => Some(&mut self.music_volume),
+            Settings::SoundVolume => Some(&mut self.sound_volume),
+            Settings::BotProfile | Settings::Vamos => None,
+        }
+    }
+
+    /// Puts each setting the menu couldn't have picked back to its value in `defaults`,
+    /// returns the labels of the ones that were
+    pub fn reset_out_of_range(&mut self, mut defaults: SettingsMenuState) -> Vec<String> {
+        let mut reset = vec![];
+        for setting in Settings::get() {
+            let (min, max) = self.range(setting);
+            let default = defaults.value_mut(setting).copied();
+            if let (Some(value), Some(default)) = (self.value_mut(setting), default) {
+                if !(min..=max).contains(value) {
+                    *value = default;
+                    reset.push(setting.get_label().to_string());
+                }
             }
-            _ => (),
         }
+
+        reset
+    }
+
+    pub fn increment(&mut self) {
+        let (min, max) = self.range(self.selected_setting);
+        if let Some(value) = self.value_mut(self.selected_setting) {
+            *value = value.circular_increment(min, max);
+        } else if self.selected_setting == Settings::BotProfile {
+            self.step_bot_profile(1);
+        }
+        self.after_step();
     }
 
     pub fn decrement(&mut self) {
-        match self.selected_setting {
-            Settings::NumberOfBots => {
-                self.number_of_bots = self
-                    .number_of_bots
-                    .circular_decrement(self.min_bots(), self.max_bots());
-            }
-            Settings::UnfairAdvantage => {
-                self.unfair_advantage = self.unfair_advantage.circular_decrement(0, 2);
-            }
-            _ => (),
+        let (min, max) = self.range(self.selected_setting);
+        if let Some(value) = self.value_mut(self.selected_setting) {
+            *value = value.circular_decrement(min, max);
+        } else if self.selected_setting == Settings::BotProfile {
+            self.step_bot_profile(-1);
         }
+        self.after_step();
     }
 }
 
@@ -67,21 +307,60 @@ impl SettingsMenuState {
 pub enum Settings {
     #[default]
     NumberOfBots,
+    BotSkill,
+    BotSlot,
+    BotProfile,
     UnfairAdvantage,
+    StartingLevel,
+    Rounds,
+    StartingHearts,
+    FireCooldown,
+    DownCooldown,
+    InvulnerabilityCooldown,
+    BulletRange,
+    AimMode,
+    MusicVolume,
+    SoundVolume,
     Vamos,
 }
 
-impl MenuOption<3> for Settings {
-    const ITEM: [Settings; 3] = [
+impl MenuOption<16> for Settings {
+    const ITEM: [Settings; 16] = [
         Settings::NumberOfBots,
+        Settings::BotSkill,
+        Settings::BotSlot,
+        Settings::BotProfile,
         Settings::UnfairAdvantage,
+        Settings::StartingLevel,
+        Settings::Rounds,
+        Settings::StartingHearts,
+        Settings::FireCooldown,
+        Settings::DownCooldown,
+        Settings::InvulnerabilityCooldown,
+        Settings::BulletRange,
+        Settings::AimMode,
+        Settings::MusicVolume,
+        Settings::SoundVolume,
         Settings::Vamos,
     ];
 
     fn get_label(&self) -> &str {
         match self {
             Settings::NumberOfBots => "Number of Bots",
+            Settings::BotSkill => "Bot Skill",
+            Settings::BotSlot => "Bot",
+            Settings::BotProfile => "Bot Profile",
             Settings::UnfairAdvantage => "Unfair Advantage",
+            Settings::StartingLevel => "Level",
+            Settings::Rounds => "Rounds",
+            Settings::StartingHearts => "Hearts",
+            Settings::FireCooldown => "Fire Cooldown",
+            Settings::DownCooldown => "Down Time",
+            Settings::InvulnerabilityCooldown => "Invulnerable Time",
+            Settings::BulletRange => "Bullet Range",
+            Settings::AimMode => "Aim",
+            Settings::MusicVolume => "Music Volume",
+            Settings::SoundVolume => "Sound Volume",
             Settings::Vamos => "¡Vamos!",
         }
     }
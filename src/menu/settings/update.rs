@@ -3,7 +3,7 @@ use super::{
     SettingDisplayMarker,
 };
 use crate::loading::command_ext::*;
-use crate::{assets, audio, game_state, input, menu, ui, AppState};
+use crate::{assets, audio, cli, config, game_state, input, levels, menu, rng, ui, AppState};
 use bevy::prelude::*;
 use leafwing_input_manager::prelude::*;
 use menu::MenuOption;
@@ -37,13 +37,22 @@ pub fn highlight_selection(
 
 pub fn update_values(
     setting_state: ResMut<SettingsMenuState>,
+    level_manifest: Res<levels::LevelManifest>,
     mut settings: Query<(&mut Text, &Settings), With<SettingDisplayMarker>>,
 ) {
     for (mut text, setting) in &mut settings {
-        text.sections[0].value = setting_state.display(setting).to_string();
+        text.sections[0].value = setting_state.display(setting, &level_manifest).to_string();
     }
 }
 
+pub fn apply_audio_settings(
+    setting_state: Res<SettingsMenuState>,
+    mut audio_settings: ResMut<audio::AudioSettings>,
+) {
+    // so volume changes can be heard right away
+    audio_settings.set_if_neq(setting_state.audio_settings());
+}
+
 pub fn handle_input(
     mut commands: Commands,
     mut setting_state: ResMut<SettingsMenuState>,
@@ -51,9 +60,13 @@ pub fn handle_input(
     game_assets: Res<assets::GameAssets>,
     player_selection: Res<menu::character_select::state::PlayerSelection>,
     mut game_state: ResMut<game_state::GameState>,
+    mut match_rules: ResMut<config::MatchRules>,
     mut audio: audio::GameAudio,
     mut axis_timer: Local<Timer>,
     time: Res<Time>,
+    mut game_rng: ResMut<rng::GameRng>,
+    game_config: Res<config::GameConfiguration>,
+    launch_options: Res<cli::LaunchOptions>,
 ) {
     let action_state = action_state.single();
 
@@ -110,18 +123,29 @@ pub fn handle_input(
         audio.play_sfx(&game_assets.sfx_1);
 
         const MIN_DIFFICULTY: f32 = 0.5;
+        *match_rules = setting_state.match_rules();
+        let number_of_bots = setting_state.number_of_bots.try_into().unwrap();
+        let bot_profiles = (0..number_of_bots)
+            .map(|slot| setting_state.slot_profile(slot).to_string())
+            .collect::<Vec<_>>();
+        game_rng.start_match(launch_options.seed.or(game_config.seed));
+        let starting_level = setting_state.starting_level as usize;
+        // starting partway through still plays the chosen number of rounds
         *game_state = game_state::GameState::initialize(
             player_selection
                 .players
                 .iter()
                 .map(|x| game_state::BurroState::from(x.clone()))
                 .collect::<Vec<_>>(),
-            setting_state.number_of_bots.try_into().unwrap(),
+            number_of_bots,
+            &bot_profiles,
             setting_state.unfair_advantage as f32 + MIN_DIFFICULTY,
+            starting_level + match_rules.rounds,
             &game_assets.burro_assets,
+            &mut game_rng.gameplay,
         );
+        game_state.current_level = starting_level;
 
-        audio.play_bgm(&game_assets.bgm_1);
         commands.load_state(AppState::LoadInGame);
     }
 }
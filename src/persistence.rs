@@ -0,0 +1,203 @@
+use crate::{
+    asset_loading, audio, bot, config, menu::settings::state::SettingsMenuState, replay, AppState,
+};
+use bevy::{ecs::system::Command, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+// bump this when a field changes meaning, files from newer versions are ignored
+const SETTINGS_VERSION: u32 = 1;
+const SETTINGS_FILE: &str = "settings.ron";
+
+pub struct PersistencePlugin;
+impl Plugin for PersistencePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            save_settings.run_if(
+                not(in_state(AppState::Initial).or_else(replay::is_replaying)).and_then(
+                    resource_changed::<config::MatchRules>()
+                        .or_else(resource_changed::<config::ConfigOverrides>())
+                        .or_else(resource_changed::<audio::AudioSettings>())
+                        .or_else(resource_changed::<SettingsMenuState>()),
+                ),
+            ),
+        );
+    }
+}
+
+/// Everything that is remembered between launches. Only the tuning values that were
+/// changed away from `assets/config/game.config.ron` are kept, they're applied over it
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct SettingsFile {
+    version: u32,
+    number_of_bots: isize,
+    bot_skill: isize,
+    /// The profile for each bot slot, slots past these use the bot skill's
+    bot_profiles: Vec<String>,
+    unfair_advantage: isize,
+    audio: audio::AudioSettings,
+    rules: config::MatchRules,
+    config: config::ConfigOverrides,
+}
+
+impl Default for SettingsFile {
+    fn default() -> Self {
+        SettingsFile {
+            version: SETTINGS_VERSION,
+            number_of_bots: 0,
+            bot_skill: 1,
+            bot_profiles: vec![],
+            unfair_advantage: 0,
+            audio: audio::AudioSettings::default(),
+            rules: config::MatchRules::default(),
+            config: config::ConfigOverrides::default(),
+        }
+    }
+}
+
+/// Reads the user's settings file into the game's resources, anything
+/// missing, unreadable or out of range falls back to the defaults
+pub struct LoadSettings;
+impl Command for LoadSettings {
+    fn apply(self, world: &mut World) {
+        let settings = read_settings_file().unwrap_or_default();
+        let mut loaded = settings.menu_state();
+        let reset = loaded.reset_out_of_range(SettingsFile::default().menu_state());
+        if !reset.is_empty() {
+            warn!(
+                "Saved settings out of range, using the defaults for: {}",
+                reset.join(", ")
+            );
+        }
+
+        // the menu's steps are the only values kept, so the rules come back out of it
+        world.insert_resource(loaded.match_rules());
+        world.insert_resource(loaded.audio_settings());
+        world.insert_resource(settings.config);
+
+        let mut settings_menu = world.resource_mut::<SettingsMenuState>();
+        settings_menu.number_of_bots = loaded.number_of_bots;
+        settings_menu.bot_skill = loaded.bot_skill;
+        settings_menu.unfair_advantage = loaded.unfair_advantage;
+
+        let mut profile_names = asset_loading::folder_assets("bots", bot::BOT_PROFILE_EXTENSION)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        profile_names.sort();
+        let missing = settings_menu.load_bot_profiles(&settings.bot_profiles, profile_names);
+        if !missing.is_empty() {
+            warn!(
+                "Saved bot profiles that aren't in assets/bots, using the bot skill's instead: {}",
+                missing.join(", ")
+            );
+        }
+    }
+}
+
+impl SettingsFile {
+    /// The settings as the menu would show them, to check them against its ranges
+    fn menu_state(&self) -> SettingsMenuState {
+        let mut menu_state = SettingsMenuState {
+            // no players yet, so any number of bots up to a full match is allowed
+            number_of_players: 0,
+            number_of_levels: 1,
+            number_of_bots: self.number_of_bots,
+            bot_skill: self.bot_skill,
+            unfair_advantage: self.unfair_advantage,
+            ..default()
+        };
+        menu_state.load_rules(&self.rules);
+        menu_state.load_audio(&self.audio);
+
+        menu_state
+    }
+}
+
+/// The game's folder in the user's config directory, where settings and replays are kept
+pub fn data_dir() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(config_dir.join(env!("CARGO_PKG_NAME")))
+}
+
+fn settings_path() -> Option<PathBuf> {
+    Some(data_dir()?.join(SETTINGS_FILE))
+}
+
+fn read_settings_file() -> Option<SettingsFile> {
+    let path = settings_path()?;
+    let contents = fs::read_to_string(&path).ok()?;
+
+    match ron::from_str::<SettingsFile>(&contents) {
+        Ok(settings) if settings.version > SETTINGS_VERSION => {
+            warn!(
+                "{} is from a newer version ({}), using default settings",
+                path.display(),
+                settings.version
+            );
+            None
+        }
+        Ok(settings) => Some(settings),
+        Err(error) => {
+            warn!(
+                "Couldn't read {}, using default settings: {}",
+                path.display(),
+                error
+            );
+            None
+        }
+    }
+}
+
+fn save_settings(
+    match_rules: Res<config::MatchRules>,
+    audio_settings: Res<audio::AudioSettings>,
+    settings_menu: Res<SettingsMenuState>,
+    config_overrides: Res<config::ConfigOverrides>,
+    mut last_saved: Local<Option<String>>,
+) {
+    let Some(path) = settings_path() else {
+        return;
+    };
+
+    let settings = SettingsFile {
+        version: SETTINGS_VERSION,
+        number_of_bots: settings_menu.number_of_bots,
+        bot_skill: settings_menu.bot_skill,
+        bot_profiles: settings_menu.bot_profiles.clone(),
+        unfair_advantage: settings_menu.unfair_advantage,
+        audio: audio_settings.clone(),
+        rules: match_rules.clone(),
+        config: config_overrides.clone(),
+    };
+
+    let contents = match ron::ser::to_string_pretty(&settings, ron::ser::PrettyConfig::default()) {
+        Ok(contents) => contents,
+        Err(error) => {
+            warn!("Couldn't save settings: {}", error);
+            return;
+        }
+    };
+
+    // the resources also change for things that aren't saved, like moving the cursor
+    // on the settings menu, so the file is only written when what's in it would change
+    let last_saved =
+        last_saved.get_or_insert_with(|| fs::read_to_string(&path).unwrap_or_default());
+    if *last_saved == contents {
+        return;
+    }
+
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    match fs::write(&path, &contents) {
+        Ok(_) => *last_saved = contents,
+        Err(error) => warn!("Couldn't save settings to {}: {}", path.display(), error),
+    }
+}
@@ -1,9 +1,14 @@
-use crate::{assets, bot, bullet, burro, direction, ZeroSignum};
+use crate::{
+    assets, bot, bullet, burro, config, direction, game_camera, input, simulation, weapon,
+    ZeroSignum,
+};
 use bevy::ecs::query::Has;
+use bevy::window::PrimaryWindow;
 use bevy::{prelude::*, reflect::TypePath};
 use bevy_rapier3d::prelude::*;
-use std::f32::consts::TAU;
-use leafwing_input_manager::prelude::*;
+use std::f32::consts::FRAC_PI_4;
+use leafwing_input_manager::{axislike::DualAxisData, prelude::*};
+use serde::{Deserialize, Serialize};
 
 pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
@@ -24,7 +29,7 @@ impl Default for Movement {
     }
 }
 
-#[derive(Actionlike, TypePath, PartialEq, Eq, Clone, Copy, Hash, Debug)]
+#[derive(Actionlike, TypePath, PartialEq, Eq, Clone, Copy, Hash, Debug, Serialize, Deserialize)]
 pub enum PlayerAction {
     Up,
     Down,
@@ -38,6 +43,9 @@ pub enum PlayerAction {
     ActionDown,
     ActionRight,
     ActionLeft,
+
+    Fire,
+    Pause,
 }
 impl PlayerAction {
     const DIRECTIONS: [Self; 4] = [
@@ -64,64 +72,109 @@ pub struct PlayerBundle {
 }
 
 impl PlayerBundle {
-    pub fn new() -> Self {
+    pub fn new(device: input::InputDevice) -> Self {
         PlayerBundle {
             input_manager: InputManagerBundle {
-                input_map: PlayerBundle::default_input_map(),
+                input_map: PlayerBundle::input_map(device),
                 action_state: ActionState::default(),
             },
         }
     }
 
-    fn default_input_map() -> InputMap<PlayerAction> {
+    fn input_map(device: input::InputDevice) -> InputMap<PlayerAction> {
         use PlayerAction::*;
         let mut input_map = InputMap::default();
 
-        input_map.set_gamepad(Gamepad { id: 0 });
+        match device {
+            input::InputDevice::Keyboard => {
+                // Movement
+                #[cfg(not(feature = "debug"))]
+                {
+                    input_map.insert(KeyCode::Up, Up);
+                    input_map.insert(KeyCode::Down, Down);
+                    input_map.insert(KeyCode::Left, Left);
+                    input_map.insert(KeyCode::Right, Right);
+                }
 
-        // Movement
-        #[cfg(not(feature = "debug"))]
-        {
-            input_map.insert(KeyCode::Up, Up);
-            input_map.insert(KeyCode::Down, Down);
-            input_map.insert(KeyCode::Left, Left);
-            input_map.insert(KeyCode::Right, Right);
-        }
+                input_map.insert(KeyCode::W, Up);
+                input_map.insert(KeyCode::Z, Up);
+                input_map.insert(KeyCode::S, Down);
+                input_map.insert(KeyCode::A, Left);
+                input_map.insert(KeyCode::Q, Left);
+                input_map.insert(KeyCode::D, Right);
 
-        input_map.insert(DualAxis::left_stick(), Move);
-        input_map.insert(DualAxis::right_stick(), Shoot);
+                // Actions
+                input_map.insert(KeyCode::J, ActionLeft);
+                input_map.insert(KeyCode::L, ActionRight);
+                input_map.insert(KeyCode::I, ActionUp);
+                input_map.insert(KeyCode::K, ActionDown);
 
-        input_map.insert(KeyCode::W, Up);
-        input_map.insert(KeyCode::Z, Up);
-        input_map.insert(GamepadButtonType::DPadUp, Up);
+                // Aim with the mouse
+                input_map.insert(MouseButton::Left, Fire);
 
-        input_map.insert(KeyCode::S, Down);
-        input_map.insert(GamepadButtonType::DPadDown, Down);
+                input_map.insert(KeyCode::Escape, Pause);
 
-        input_map.insert(KeyCode::A, Left);
-        input_map.insert(KeyCode::Q, Left);
-        input_map.insert(GamepadButtonType::DPadLeft, Left);
+                //      input_map.insert(KeyCode::Space, Action);
+                //      input_map.insert(KeyCode::Return, Action);
+            }
+            input::InputDevice::Gamepad(gamepad) => {
+                input_map.set_gamepad(gamepad);
 
-        input_map.insert(KeyCode::D, Right);
-        input_map.insert(GamepadButtonType::DPadRight, Right);
+                // Movement
+                input_map.insert(DualAxis::left_stick(), Move);
+                input_map.insert(DualAxis::right_stick(), Shoot);
 
-        // Actions
-        input_map.insert(KeyCode::J, ActionLeft);
-        input_map.insert(GamepadButtonType::West, ActionLeft);
+                input_map.insert(GamepadButtonType::DPadUp, Up);
+                input_map.insert(GamepadButtonType::DPadDown, Down);
+                input_map.insert(GamepadButtonType::DPadLeft, Left);
+                input_map.insert(GamepadButtonType::DPadRight, Right);
 
-        input_map.insert(KeyCode::L, ActionRight);
-        input_map.insert(GamepadButtonType::East, ActionRight);
+                // Actions
+                input_map.insert(GamepadButtonType::West, ActionLeft);
+                input_map.insert(GamepadButtonType::East, ActionRight);
+                input_map.insert(GamepadButtonType::North, ActionUp);
+                input_map.insert(GamepadButtonType::South, ActionDown);
 
-        input_map.insert(KeyCode::I, ActionUp);
-        input_map.insert(GamepadButtonType::North, ActionUp);
+                input_map.insert(GamepadButtonType::Start, Pause);
+            }
+        }
+
+        input_map
+    }
+}
 
-        input_map.insert(KeyCode::K, ActionDown);
-        input_map.insert(GamepadButtonType::South, ActionDown);
+/// Which directions a burro is allowed to fire in
+#[derive(Default, Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum AimMode {
+    #[default]
+    Cardinal,
+    EightWay,
+    Free,
+}
 
-        //      input_map.insert(KeyCode::Space, Action);
-        //      input_map.insert(KeyCode::Return, Action);
+impl AimMode {
+    /// Turns a raw aim on the ground plane into a fire direction for this mode.
+    /// Returns None if the aim is too small to count as firing.
+    pub fn snap(self, aim: Vec3) -> Option<Vec3> {
+        let aim = Vec3::new(aim.x, 0.0, aim.z);
+        if aim.length() < 0.5 {
+            return None;
+        }
 
-        input_map
+        Some(match self {
+            AimMode::Cardinal => {
+                if aim.x.abs() >= aim.z.abs() {
+                    Vec3::new(aim.x.signum(), 0.0, 0.0)
+                } else {
+                    Vec3::new(0.0, 0.0, aim.z.signum())
+                }
+            }
+            AimMode::EightWay => {
+                let angle = (aim.z.atan2(aim.x) / FRAC_PI_4).round() * FRAC_PI_4;
+                Vec3::new(angle.cos(), 0.0, angle.sin())
+            }
+            AimMode::Free => aim.normalize(),
+        })
     }
 }
 
@@ -141,56 +194,64 @@ pub fn handle_input(
         Has<bot::Bot>,
     )>,
     mut bullet_event_writer: EventWriter<bullet::BulletEvent>,
+    game_assets: Res<assets::GameAssets>,
+    game_config: Res<config::GameConfiguration>,
+    match_rules: Res<config::MatchRules>,
+    weapons: Res<Assets<weapon::Weapon>>,
 ) {
     for (entity, action_state, transform, mut burro, mut movement, has_bot) in &mut players {
         let mut direction = direction::Direction::NEUTRAL;
-        let mut facing = None;
 
         if burro.is_down {
             continue;
         }
 
-        let mut pressed_up = false;
-        let mut pressed_down = false;
-        let mut pressed_left = false;
-        let mut pressed_right = false;
+        let mut aim = Vec3::ZERO;
+        if action_state.pressed(PlayerAction::ActionUp) {
+            aim.x += 1.0;
+        }
+        if action_state.pressed(PlayerAction::ActionDown) {
+            aim.x -= 1.0;
+        }
+        if action_state.pressed(PlayerAction::ActionLeft) {
+            aim.z -= 1.0;
+        }
+        if action_state.pressed(PlayerAction::ActionRight) {
+            aim.z += 1.0;
+        }
+
         if action_state.pressed(PlayerAction::Shoot) {
             let axis_pair = action_state.clamped_axis_pair(PlayerAction::Shoot).unwrap();
-            pressed_up = axis_pair.y() > 0.5;
-            pressed_down = axis_pair.y() < -0.5;
-            pressed_right = axis_pair.x() > 0.5;
-            pressed_left = axis_pair.x() < -0.5;
+            if axis_pair.xy().length() > 0.5 {
+                aim = Vec3::new(axis_pair.y(), 0.0, axis_pair.x());
+            }
         }
 
-        let mut fire = None;
-        if action_state.pressed(PlayerAction::ActionUp) || pressed_up {
-            facing = Some(Quat::from_axis_angle(Vec3::Y, 0.0));
-            fire = Some(Vec3::new(1.0, 0.0, 0.0));
-        } else if action_state.pressed(PlayerAction::ActionDown) || pressed_down {
-            facing = Some(Quat::from_axis_angle(Vec3::Y, TAU * 0.5));
-            fire = Some(Vec3::new(-1.0, 0.0, 0.0));
-        } else if action_state.pressed(PlayerAction::ActionLeft) || pressed_left {
-            facing = Some(Quat::from_axis_angle(Vec3::Y, TAU * 0.25));
-            fire = Some(Vec3::new(0.0, 0.0, -1.0));
-        } else if action_state.pressed(PlayerAction::ActionRight) || pressed_right {
-            facing = Some(Quat::from_axis_angle(Vec3::Y, TAU * 0.75));
-            fire = Some(Vec3::new(0.0, 0.0, 1.0));
+        if action_state.pressed(PlayerAction::Fire) {
+            if let Some(axis_pair) = action_state.axis_pair(PlayerAction::Fire) {
+                aim = Vec3::new(axis_pair.x(), 0.0, axis_pair.y());
+            }
         }
 
+        let fire = match_rules.aim_mode.snap(aim);
+        let facing = fire.map(|fire| Quat::from_axis_angle(Vec3::Y, (-fire.z).atan2(fire.x)));
+
         if burro.can_fire() && fire.is_some() {
-            bullet_event_writer.send(bullet::BulletEvent {
-                source: entity,
-                speed: 12.0,       //burro.bullet_speed,
-                time_to_live: 3.0, //burro.bullet_time_alive,
-                position: transform.translation,
-                direction: fire.unwrap(),
-                bullet_type: if burro.is_mechaburro {
-                    bullet::BulletType::Laser
-                } else {
-                    bullet::BulletType::Candy
-                },
-            });
-            burro.fire();
+            // nothing is fired until the weapon has loaded, the names are checked with the config
+            let weapon_name = game_config.weapon_name(burro.is_mechaburro);
+            let weapon = game_assets
+                .weapons
+                .get(weapon_name)
+                .and_then(|handle| weapons.get(handle).map(|weapon| (handle, weapon)));
+            if let Some((handle, weapon)) = weapon {
+                bullet_event_writer.send(bullet::BulletEvent {
+                    source: entity,
+                    position: transform.translation,
+                    direction: fire.unwrap(),
+                    weapon: handle.clone(),
+                });
+                burro.fire(weapon.cooldown * match_rules.fire_cooldown_scale);
+            }
         }
 
         for input_direction in PlayerAction::DIRECTIONS {
@@ -210,8 +271,38 @@ pub fn handle_input(
     }
 }
 
+/// Stores the aim from the burro toward where the cursor hits the ground as the fire
+/// action's axis pair, so everything handle_input needs is in the action state
+pub fn aim_with_cursor(
+    mut players: Query<(&mut ActionState<PlayerAction>, &Transform), Without<bot::Bot>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<game_camera::PanOrbitCamera>>,
+) {
+    let cursor_ray = windows
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .zip(cameras.get_single().ok())
+        .and_then(|(cursor, (camera, camera_transform))| {
+            camera.viewport_to_world(camera_transform, cursor)
+        });
+
+    for (mut action_state, transform) in &mut players {
+        if !action_state.pressed(PlayerAction::Fire) {
+            continue;
+        }
+
+        let aim = cursor_ray.and_then(|ray| {
+            ray.intersect_plane(transform.translation, Vec3::Y)
+                .map(|distance| ray.get_point(distance) - transform.translation)
+        });
+        action_state.action_data_mut(PlayerAction::Fire).axis_pair =
+            aim.map(|aim| DualAxisData::new(aim.x, aim.z));
+    }
+}
+
 pub fn move_player(
-    time: Res<Time>,
+    fixed_time: Res<FixedTime>,
     mut burros: Query<(
         Entity,
         &mut KinematicCharacterController,
@@ -223,33 +314,35 @@ pub fn move_player(
     )>,
     mut animations: Query<(&mut AnimationPlayer, &assets::AnimationLink)>,
     game_assets: Res<assets::GameAssets>,
+    game_config: Res<config::GameConfiguration>,
 ) {
+    let delta = simulation::tick_delta(&fixed_time);
     for (entity, mut controller, controller_output, mut transform, mut burro, movement, is_bot) in
         burros.iter_mut()
     {
-        let speed: f32 = burro.speed;
+        let speed: f32 = game_config.burro_speed;
         let friction: f32 = burro.friction;
         let gravity: Vec3 = Vec3::new(0.0, -5.0, 0.0);
 
-        burro.velocity *= friction.powf(time.delta_seconds());
+        burro.velocity *= friction.powf(delta);
         //        burro.velocity += (Vec3::X * speed) * time.delta_seconds();
 
         if !burro.is_down {
             match movement.movement {
                 Movement::Analog(direction) => {
                     let acceleration = Vec3::new(direction.y, 0.0, direction.x);
-                    burro.velocity += (acceleration * speed) * time.delta_seconds();
+                    burro.velocity += (acceleration * speed) * delta;
                 }
                 Movement::Normal(direction) => {
                     let acceleration = Vec3::from(direction).zero_signum();
-                    burro.velocity += (acceleration * speed) * time.delta_seconds();
+                    burro.velocity += (acceleration * speed) * delta;
                 }
             }
         }
 
         burro.velocity = burro.velocity.clamp_length_max(speed);
 
-        let new_translation = (gravity + burro.velocity) * time.delta_seconds();
+        let new_translation = (gravity + burro.velocity) * delta;
         let new_position = new_translation + transform.translation;
 
         let angle = (-(new_position.z - transform.translation.z))
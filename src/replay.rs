@@ -0,0 +1,413 @@
+use crate::loading::command_ext::StateSetter;
+use crate::{
+    burro, config, game_state, input,
+    menu::{character_select, title_screen},
+    persistence,
+    player::{self, PlayerAction},
+    rng, simulation, AppState, IngameState,
+};
+use bevy::{ecs::system::Command, prelude::*};
+use leafwing_input_manager::{axislike::DualAxisData, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+// bump this when the format changes, replays from newer versions aren't played
+const REPLAY_VERSION: u32 = 1;
+const REPLAY_DIR: &str = "replays";
+const REPLAY_EXTENSION: &str = "replay.ron";
+
+pub struct ReplayPlugin;
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnExit(AppState::MechaPicker),
+            start_recording.run_if(not(is_replaying)),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                record_inputs.run_if(resource_exists::<ReplayRecorder>()),
+                play_inputs.run_if(is_replaying),
+            )
+                .in_set(simulation::SimulationSet::Players)
+                .after(player::aim_with_cursor)
+                .before(player::handle_input)
+                .run_if(in_state(AppState::InGame)),
+        )
+        .add_systems(
+            OnEnter(IngameState::ScoreDisplay),
+            save_replay.run_if(resource_exists::<ReplayRecorder>()),
+        );
+    }
+}
+
+/// What a player was doing on a tick, stored whenever it changes
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+struct RecordedInput {
+    pressed: Vec<PlayerAction>,
+    axis_pairs: Vec<(PlayerAction, [f32; 2])>,
+}
+
+impl RecordedInput {
+    fn capture(action_state: &ActionState<PlayerAction>) -> Self {
+        let mut input = RecordedInput::default();
+        for action in PlayerAction::variants().filter(|action| Self::is_recorded(*action)) {
+            if action_state.pressed(action) {
+                input.pressed.push(action);
+            }
+            if let Some(axis_pair) = action_state.axis_pair(action) {
+                input
+                    .axis_pairs
+                    .push((action, [axis_pair.x(), axis_pair.y()]));
+            }
+        }
+
+        input
+    }
+
+    fn apply(&self, action_state: &mut ActionState<PlayerAction>) {
+        for action in PlayerAction::variants().filter(|action| Self::is_recorded(*action)) {
+            if self.pressed.contains(&action) {
+                action_state.press(action);
+            } else {
+                action_state.release(action);
+            }
+
+            action_state.action_data_mut(action).axis_pair = self
+                .axis_pairs
+                .iter()
+                .find(|(recorded, _)| *recorded == action)
+                .map(|(_, [x, y])| DualAxisData::new(*x, *y));
+        }
+    }
+
+    // pausing doesn't change the round so it's left to the actual players
+    fn is_recorded(action: PlayerAction) -> bool {
+        action != PlayerAction::Pause
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct InputChange {
+    tick: u32,
+    player: usize,
+    input: RecordedInput,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ReplayBurro {
+    player: usize,
+    selected_burro: usize,
+    is_bot: bool,
+    outline_color: [f32; 4],
+}
+
+/// Everything needed to play a round back, written when the round ends
+#[derive(Serialize, Deserialize)]
+struct ReplayFile {
+    version: u32,
+    match_seed: u64,
+    round_seed: u64,
+    level: usize,
+    difficulty: f32,
+    mechaburro: Option<usize>,
+    burros: Vec<ReplayBurro>,
+    rules: config::MatchRules,
+    config: config::GameConfiguration,
+    ticks: u32,
+    inputs: Vec<InputChange>,
+}
+
+#[derive(Resource)]
+struct ReplayRecorder {
+    replay: ReplayFile,
+    last_inputs: HashMap<usize, RecordedInput>,
+}
+
+/// Present while a replay is playing, feeds the recorded inputs to the burros
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    replay: ReplayFile,
+    tick: u32,
+    next_input: usize,
+    inputs: HashMap<usize, RecordedInput>,
+}
+
+impl ReplayPlayback {
+    pub fn round_seed(&self) -> u64 {
+        self.replay.round_seed
+    }
+
+    /// The selected burro that was the mechaburro in the recorded round
+    pub fn mechaburro(&self) -> Option<usize> {
+        self.replay.mechaburro
+    }
+}
+
+pub fn is_replaying(playback: Option<Res<ReplayPlayback>>) -> bool {
+    playback.is_some()
+}
+
+/// Loads a replay file and sets the game up to play it back
+pub struct LoadReplay(pub PathBuf);
+impl Command for LoadReplay {
+    fn apply(self, world: &mut World) {
+        let replay = match read_replay(&self.0) {
+            Ok(replay) => replay,
+            Err(error) => {
+                warn!("Couldn't play {}: {}", self.0.display(), error);
+                StateSetter(AppState::TitleScreen).apply(world);
+                return;
+            }
+        };
+
+        info!(
+            "Replaying level {} with match seed {} and round seed {}",
+            replay.level, replay.match_seed, replay.round_seed
+        );
+
+        // the round needs the burro textures and the menu fonts and music
+        character_select::loader::CharacterSelectLoader.apply(world);
+        title_screen::loader::TitleScreenLoader.apply(world);
+
+        insert_playback(world, replay);
+
+        StateSetter(AppState::LoadInGame).apply(world);
+    }
+}
+
+/// Sets up the match, the rules and the configuration the replay was recorded with
+fn insert_playback(world: &mut World, replay: ReplayFile) {
+    let burros = replay
+        .burros
+        .iter()
+        .map(|burro| game_state::BurroState {
+            player: burro.player,
+            selected_burro: burro.selected_burro,
+            outline_color: Color::rgba(
+                burro.outline_color[0],
+                burro.outline_color[1],
+                burro.outline_color[2],
+                burro.outline_color[3],
+            ),
+            score: 0,
+            is_bot: burro.is_bot,
+            bot_profile: None,
+            hearts: vec![],
+            input_device: if burro.player == 0 {
+                input::InputDevice::Keyboard
+            } else {
+                input::InputDevice::Gamepad(Gamepad { id: burro.player })
+            },
+        })
+        .collect();
+
+    // only the recorded round is played, then the match ends
+    world.insert_resource(game_state::GameState {
+        burros,
+        dead_burros: vec![],
+        current_level: replay.level,
+        rounds: replay.level + 1,
+        difficulty: replay.difficulty,
+    });
+    world.insert_resource(rng::GameRng::new(replay.match_seed));
+    world.insert_resource(replay.rules.clone());
+    world.insert_resource(replay.config.clone());
+    world.insert_resource(ReplayPlayback {
+        replay,
+        tick: 0,
+        next_input: 0,
+        inputs: HashMap::new(),
+    });
+}
+
+fn read_replay(path: &Path) -> Result<ReplayFile, String> {
+    let contents = fs::read_to_string(path).map_err(|error| error.to_string())?;
+    let replay: ReplayFile = ron::from_str(&contents).map_err(|error| error.to_string())?;
+    if replay.version > REPLAY_VERSION {
+        return Err(format!("it's from a newer version ({})", replay.version));
+    }
+
+    Ok(replay)
+}
+
+fn start_recording(
+    mut commands: Commands,
+    game_state: Res<game_state::GameState>,
+    game_rng: Res<rng::GameRng>,
+    match_rules: Res<config::MatchRules>,
+    game_config: Res<config::GameConfiguration>,
+    burros: Query<&burro::Burro>,
+) {
+    let mechaburro = burros
+        .iter()
+        .find(|burro| burro.is_mechaburro)
+        .map(|burro| burro.selected_burro);
+
+    commands.insert_resource(ReplayRecorder {
+        replay: ReplayFile {
+            version: REPLAY_VERSION,
+            match_seed: game_rng.seed(),
+            round_seed: game_rng.round_seed(),
+            level: game_state.current_level,
+            difficulty: game_state.difficulty,
+            mechaburro,
+            burros: game_state
+                .burros
+                .iter()
+                .map(|burro| ReplayBurro {
+                    player: burro.player,
+                    selected_burro: burro.selected_burro,
+                    is_bot: burro.is_bot,
+                    outline_color: burro.outline_color.as_rgba_f32(),
+                })
+                .collect(),
+            rules: match_rules.clone(),
+            config: game_config.clone(),
+            ticks: 0,
+            inputs: vec![],
+        },
+        last_inputs: HashMap::new(),
+    });
+}
+
+fn record_inputs(
+    mut recorder: ResMut<ReplayRecorder>,
+    players: Query<(&game_state::PlayerMarker, &ActionState<PlayerAction>)>,
+) {
+    let recorder = &mut *recorder;
+    let tick = recorder.replay.ticks;
+    for (player, action_state) in &players {
+        let input = RecordedInput::capture(action_state);
+        if recorder.last_inputs.get(&player.0) != Some(&input) {
+            recorder.replay.inputs.push(InputChange {
+                tick,
+                player: player.0,
+                input: input.clone(),
+            });
+            recorder.last_inputs.insert(player.0, input);
+        }
+    }
+
+    recorder.replay.ticks += 1;
+}
+
+fn play_inputs(
+    mut playback: ResMut<ReplayPlayback>,
+    mut players: Query<(&game_state::PlayerMarker, &mut ActionState<PlayerAction>)>,
+) {
+    let playback = &mut *playback;
+    while let Some(change) = playback
+        .replay
+        .inputs
+        .get(playback.next_input)
+        .filter(|change| change.tick <= playback.tick)
+    {
+        playback.inputs.insert(change.player, change.input.clone());
+        playback.next_input += 1;
+    }
+
+    for (player, mut action_state) in &mut players {
+        if let Some(input) = playback.inputs.get(&player.0) {
+            input.apply(&mut action_state);
+        }
+    }
+
+    playback.tick += 1;
+}
+
+fn save_replay(mut commands: Commands, recorder: Res<ReplayRecorder>) {
+    commands.remove_resource::<ReplayRecorder>();
+
+    let Some(dir) = persistence::data_dir() else {
+        return;
+    };
+    let dir = dir.join(REPLAY_DIR);
+    let replay = &recorder.replay;
+    let path = dir.join(format!(
+        "{}_{:02}.{}",
+        replay.match_seed, replay.level, REPLAY_EXTENSION
+    ));
+
+    // one input change per line keeps the file readable without getting huge
+    let pretty = ron::ser::PrettyConfig::new().depth_limit(2);
+    let contents = match ron::ser::to_string_pretty(replay, pretty) {
+        Ok(contents) => contents,
+        Err(error) => {
+            warn!("Couldn't save replay: {}", error);
+            return;
+        }
+    };
+
+    let _ = fs::create_dir_all(&dir);
+    match fs::write(&path, contents) {
+        Ok(_) => info!("Saved replay to {}", path.display()),
+        Err(error) => warn!("Couldn't save replay to {}: {}", path.display(), error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::asset::LoadState;
+
+    #[test]
+    fn recorded_config_survives_the_first_config_load() {
+        let recorded = config::GameConfiguration {
+            burro_speed: 12.5,
+            seed: Some(3),
+            ..default()
+        };
+        let replay = ReplayFile {
+            version: REPLAY_VERSION,
+            match_seed: 3,
+            round_seed: 3,
+            level: 0,
+            difficulty: 1.0,
+            mechaburro: None,
+            burros: vec![],
+            rules: config::MatchRules::default(),
+            config: recorded.clone(),
+            ticks: 0,
+            inputs: vec![],
+        };
+        let path = std::env::temp_dir().join(format!(
+            "{}_{}.{}",
+            env!("CARGO_PKG_NAME"),
+            std::process::id(),
+            REPLAY_EXTENSION
+        ));
+        fs::write(&path, ron::to_string(&replay).unwrap()).unwrap();
+        let replay = read_replay(&path);
+        let _ = fs::remove_file(&path);
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), config::ConfigPlugin))
+            .insert_resource(config::GameConfiguration::default());
+        insert_playback(&mut app.world, replay.unwrap());
+
+        // game.config.ron loads in the background, its first read comes a frame after
+        let asset_server = app.world.resource::<AssetServer>().clone();
+        for _ in 0..1000 {
+            app.update();
+            if asset_server.get_load_state(config::CONFIGURATION_PATH) == LoadState::Loaded {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        app.update();
+        app.update();
+
+        assert_eq!(
+            asset_server.get_load_state(config::CONFIGURATION_PATH),
+            LoadState::Loaded
+        );
+        let game_config = app.world.resource::<config::GameConfiguration>();
+        assert_eq!(game_config.burro_speed, recorded.burro_speed);
+        assert_eq!(game_config.seed, recorded.seed);
+    }
+}
@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+// offsets the cosmetic stream so it doesn't mirror the gameplay one
+const COSMETIC_STREAM: u64 = 0x9e37_79b9_7f4a_7c15;
+
+pub struct RngPlugin;
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameRng>();
+    }
+}
+
+/// All of the game's randomness comes from here so a match can be replayed from its seed.
+/// Anything that changes how a match plays out uses `gameplay`, effects that only change
+/// how it looks use `cosmetic` so they can't shift the gameplay rolls.
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u64,
+    round_seed: u64,
+    pub gameplay: StdRng,
+    pub cosmetic: StdRng,
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng::new(rand::random())
+    }
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        GameRng {
+            seed,
+            round_seed: seed,
+            gameplay: StdRng::seed_from_u64(seed),
+            cosmetic: StdRng::seed_from_u64(seed ^ COSMETIC_STREAM),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn round_seed(&self) -> u64 {
+        self.round_seed
+    }
+
+    /// Reseeds both streams for a new match, picking a fresh seed if none is given
+    pub fn start_match(&mut self, seed: Option<u64>) {
+        *self = GameRng::new(seed.unwrap_or_else(rand::random));
+        info!("Match seed: {}", self.seed);
+    }
+
+    /// Reseeds both streams for a new round so each round can be replayed on its own.
+    /// Without a seed the next one is drawn from the gameplay stream.
+    pub fn start_round(&mut self, round_seed: Option<u64>) {
+        let round_seed = round_seed.unwrap_or_else(|| self.gameplay.gen());
+        let seed = self.seed;
+        *self = GameRng::new(round_seed);
+        self.seed = seed;
+    }
+}
@@ -44,6 +44,7 @@ pub struct SceneOnComplete {
                 &Res<Assets<Gltf>>,
                 &Res<assets::GameAssets>,
                 &Res<game_state::GameState>,
+                &floor::FloorManager,
             ) + Send
             + Sync
             + 'static,
@@ -56,6 +57,7 @@ impl SceneOnComplete {
                 &Res<Assets<Gltf>>,
                 &Res<assets::GameAssets>,
                 &Res<game_state::GameState>,
+                &floor::FloorManager,
             ) + Send
             + Sync
             + 'static,
@@ -107,8 +109,15 @@ pub fn run_hooks(
 
         if scene_manager.instance_is_ready(**instance) {
             cmds.entity(entity).insert(SceneHooked);
+            floor_manager.build_nav_grid();
             if let Some(on_complete) = maybe_on_complete {
-                (on_complete.on_complete)(&mut cmds, &gltfs, &game_assets, &game_state);
+                (on_complete.on_complete)(
+                    &mut cmds,
+                    &gltfs,
+                    &game_assets,
+                    &game_state,
+                    &floor_manager,
+                );
             }
         }
     }
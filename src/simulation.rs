@@ -0,0 +1,128 @@
+use bevy::{prelude::*, transform::TransformSystem};
+use bevy_rapier3d::prelude::*;
+
+/// How many gameplay ticks run per second, regardless of frame rate
+pub const TICKS_PER_SECOND: f32 = 60.0;
+
+pub struct SimulationPlugin;
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FixedTime::new_from_secs(1.0 / TICKS_PER_SECOND))
+            .insert_resource(RapierConfiguration {
+                timestep_mode: TimestepMode::Fixed {
+                    dt: 1.0 / TICKS_PER_SECOND,
+                    substeps: 1,
+                },
+                ..default()
+            })
+            .configure_sets(
+                FixedUpdate,
+                (
+                    SimulationSet::Restore,
+                    SimulationSet::Players,
+                    SimulationSet::Bullets,
+                    SimulationSet::Burros,
+                    PhysicsSet::SyncBackend,
+                    PhysicsSet::SyncBackendFlush,
+                    PhysicsSet::StepSimulation,
+                    PhysicsSet::Writeback,
+                    SimulationSet::Record,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::SyncBackend)
+                        .in_set(PhysicsSet::SyncBackend),
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::SyncBackendFlush)
+                        .in_set(PhysicsSet::SyncBackendFlush),
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::StepSimulation)
+                        .in_set(PhysicsSet::StepSimulation),
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::Writeback)
+                        .in_set(PhysicsSet::Writeback),
+                ),
+            )
+            .add_systems(
+                FixedUpdate,
+                restore_transforms.in_set(SimulationSet::Restore),
+            )
+            .add_systems(FixedUpdate, record_transforms.in_set(SimulationSet::Record))
+            .add_systems(
+                PostUpdate,
+                interpolate_transforms.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// Order of the gameplay systems within a tick, physics runs between `Burros` and `Record`
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
+pub enum SimulationSet {
+    Restore,
+    Players,
+    Bullets,
+    Burros,
+    Record,
+}
+
+/// Smooths the movement of an entity that's moved by the simulation. Its translation
+/// is drawn between the last two ticks and put back to the simulated one before a tick.
+#[derive(Component)]
+pub struct Interpolated {
+    previous: Vec3,
+    current: Vec3,
+}
+
+impl Interpolated {
+    pub fn new(translation: Vec3) -> Self {
+        Interpolated {
+            previous: translation,
+            current: translation,
+        }
+    }
+}
+
+pub trait SimulationEventExt {
+    /// Registers an event that is sent and read by simulation systems. These are kept
+    /// for two ticks rather than two frames so none are lost when frames run without a tick.
+    fn add_simulation_event<T: Event>(&mut self) -> &mut Self;
+}
+
+impl SimulationEventExt for App {
+    fn add_simulation_event<T: Event>(&mut self) -> &mut Self {
+        self.init_resource::<Events<T>>().add_systems(
+            FixedUpdate,
+            Events::<T>::update_system.after(SimulationSet::Record),
+        )
+    }
+}
+
+/// Seconds covered by one tick
+pub fn tick_delta(fixed_time: &FixedTime) -> f32 {
+    fixed_time.period.as_secs_f32()
+}
+
+fn restore_transforms(mut entities: Query<(&mut Transform, &mut Interpolated)>) {
+    for (mut transform, mut interpolated) in &mut entities {
+        transform.translation = interpolated.current;
+        interpolated.previous = interpolated.current;
+    }
+}
+
+fn record_transforms(mut entities: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in &mut entities {
+        interpolated.current = transform.translation;
+    }
+}
+
+fn interpolate_transforms(
+    fixed_time: Res<FixedTime>,
+    mut entities: Query<(&mut Transform, &Interpolated)>,
+) {
+    // how far into the next tick this frame is drawn
+    let alpha = fixed_time.accumulated().as_secs_f32() / tick_delta(&fixed_time);
+    let alpha = alpha.clamp(0.0, 1.0);
+    for (mut transform, interpolated) in &mut entities {
+        transform.translation = interpolated.previous.lerp(interpolated.current, alpha);
+    }
+}
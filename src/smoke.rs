@@ -1,6 +1,6 @@
-use crate::{cleanup, AppState};
+use crate::{cleanup, is_leaving_match, rng, AppState};
 use bevy::prelude::*;
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
 pub struct SmokePlugin;
 impl Plugin for SmokePlugin {
@@ -9,7 +9,14 @@ impl Plugin for SmokePlugin {
             Update,
             (handle_smokers, handle_smokes).run_if(in_state(AppState::InGame)),
         )
-        .add_systems(OnExit(AppState::InGame), cleanup::<CleanupMarker>);
+        .add_systems(
+            OnExit(AppState::InGame),
+            cleanup::<CleanupMarker>.run_if(is_leaving_match),
+        )
+        .add_systems(
+            OnExit(AppState::Pause),
+            cleanup::<CleanupMarker>.run_if(is_leaving_match),
+        );
     }
 }
 
@@ -65,7 +72,9 @@ fn handle_smokers(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     time: Res<Time>,
+    mut game_rng: ResMut<rng::GameRng>,
 ) {
+    let rng = &mut game_rng.cosmetic;
     for (mut smoker, transform) in smokers.iter_mut() {
         smoker.cooldown -= time.delta_seconds();
         smoker.cooldown = smoker.cooldown.clamp(-1.0, 3.0);
@@ -73,8 +82,8 @@ fn handle_smokers(
             smoker.cooldown = 0.2;
 
             for _ in 0..3 {
-                let inner_mesh_x = thread_rng().gen_range(-25..25) as f32 / 100.0;
-                let inner_mesh_z = thread_rng().gen_range(-25..25) as f32 / 100.0;
+                let inner_mesh_x = rng.gen_range(-25..25) as f32 / 100.0;
+                let inner_mesh_z = rng.gen_range(-25..25) as f32 / 100.0;
 
                 let color = Color::rgba(0.3, 0.3, 0.3, 0.7 + inner_mesh_x.abs());
 
@@ -0,0 +1,158 @@
+use crate::bullet::WallCollision;
+use bevy::{
+    asset::{AssetLoader, AssetPath, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::BoxedFuture,
+};
+use bevy_kira_audio::AudioSource;
+use serde::Deserialize;
+
+pub const WEAPON_EXTENSION: &str = "weapon.ron";
+
+pub struct WeaponPlugin;
+impl Plugin for WeaponPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Weapon>()
+            .init_asset_loader::<WeaponLoader>();
+    }
+}
+
+/// A projectile weapon loaded from a `*.weapon.ron` file in `assets/weapons`
+#[derive(TypeUuid, TypePath, Clone)]
+#[uuid = "4d1b6a3e-3b8f-4f59-9a43-2c8d1e7f5b60"]
+pub struct Weapon {
+    pub mesh: Handle<Mesh>,
+    pub color: Color,
+    pub speed: f32,
+    pub lifetime: f32,
+    pub cooldown: f32,
+    pub spread_count: usize,
+    pub spread_angle: f32,
+    pub knockback: f32,
+    pub sound: Handle<AudioSource>,
+    pub hit_sound: Handle<AudioSource>,
+    pub hit_color: Color,
+    pub smoke_on_hit: bool,
+    pub spin: bool,
+    pub light: Option<Color>,
+    pub wall_collision: WallCollision,
+}
+
+impl Weapon {
+    /// Directions of every projectile fired in one shot, fanned out evenly around `direction`
+    pub fn spread(&self, direction: Vec3) -> Vec<Vec3> {
+        let count = self.spread_count.max(1);
+        if count == 1 {
+            return vec![direction];
+        }
+
+        let step = self.spread_angle.to_radians() / (count - 1) as f32;
+        let start = -self.spread_angle.to_radians() / 2.0;
+        (0..count)
+            .map(|i| Quat::from_rotation_y(start + step * i as f32) * direction)
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct WeaponDefinition {
+    mesh: String,
+    color: [f32; 3],
+    speed: f32,
+    lifetime: f32,
+    cooldown: f32,
+    #[serde(default = "default_spread_count")]
+    spread_count: usize,
+    #[serde(default)]
+    spread_angle: f32,
+    knockback: f32,
+    sound: String,
+    hit_sound: String,
+    hit_color: [f32; 3],
+    #[serde(default)]
+    smoke_on_hit: bool,
+    #[serde(default)]
+    spin: bool,
+    #[serde(default)]
+    light: Option<[f32; 3]>,
+    #[serde(default = "default_wall_collision")]
+    wall_collision: WallCollision,
+}
+
+impl WeaponDefinition {
+    fn to_weapon(
+        &self,
+        mesh: Handle<Mesh>,
+        sound: Handle<AudioSource>,
+        hit_sound: Handle<AudioSource>,
+    ) -> Weapon {
+        Weapon {
+            mesh,
+            color: Color::rgb(self.color[0], self.color[1], self.color[2]),
+            speed: self.speed,
+            lifetime: self.lifetime,
+            cooldown: self.cooldown,
+            spread_count: self.spread_count,
+            spread_angle: self.spread_angle,
+            knockback: self.knockback,
+            sound,
+            hit_sound,
+            hit_color: Color::rgb(self.hit_color[0], self.hit_color[1], self.hit_color[2]),
+            smoke_on_hit: self.smoke_on_hit,
+            spin: self.spin,
+            light: self.light.map(|c| Color::rgb(c[0], c[1], c[2])),
+            wall_collision: self.wall_collision,
+        }
+    }
+}
+
+/// Reads a weapon without its mesh and sounds, for the headless simulation
+pub fn read_weapon(bytes: &[u8]) -> Result<Weapon, ron::error::SpannedError> {
+    let definition: WeaponDefinition = ron::de::from_bytes(bytes)?;
+    Ok(definition.to_weapon(default(), default(), default()))
+}
+
+fn default_spread_count() -> usize {
+    1
+}
+
+fn default_wall_collision() -> WallCollision {
+    WallCollision::Stop
+}
+
+#[derive(Default)]
+pub struct WeaponLoader;
+impl AssetLoader for WeaponLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let definition: WeaponDefinition = ron::de::from_bytes(bytes)?;
+
+            let mesh_path = AssetPath::from(definition.mesh.as_str());
+            let sound_path = AssetPath::from(definition.sound.as_str());
+            let hit_sound_path = AssetPath::from(definition.hit_sound.as_str());
+
+            let weapon = definition.to_weapon(
+                load_context.get_handle(mesh_path.clone()),
+                load_context.get_handle(sound_path.clone()),
+                load_context.get_handle(hit_sound_path.clone()),
+            );
+
+            load_context.set_default_asset(
+                LoadedAsset::new(weapon)
+                    .with_dependency(mesh_path)
+                    .with_dependency(sound_path)
+                    .with_dependency(hit_sound_path),
+            );
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[WEAPON_EXTENSION]
+    }
+}